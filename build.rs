@@ -1,4 +1,68 @@
 use librojo::cli;
+use std::path::Path;
+
+/// Port the plugin connects to unless `RBX_MCP_PORT` is set at build time, must match the server's default
+const DEFAULT_PLUGIN_PORT: u16 = 44755;
+/// Host the plugin connects to unless `RBX_MCP_PLUGIN_HOST` is set at build time
+const DEFAULT_PLUGIN_HOST: &str = "localhost";
+
+fn json_string(path: &Path) -> String {
+    format!("{:?}", path.display().to_string())
+}
+
+/// Host name or IP address for the plugin's URL, IPv6 addresses in brackets
+fn plugin_host() -> String {
+    let Ok(host) = std::env::var("RBX_MCP_PLUGIN_HOST") else {
+        return DEFAULT_PLUGIN_HOST.to_string();
+    };
+    if let Ok(std::net::IpAddr::V6(address)) = host.parse() {
+        return format!("[{address}]");
+    }
+    let valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '[' | ']' | ':'));
+    if !valid {
+        panic!("RBX_MCP_PLUGIN_HOST must be a host name or IP address, got {host:?}");
+    }
+    host
+}
+
+/// Writes a project that adds `PluginPort` and `PluginHost` modules to the plugin sources, so the address the
+/// plugin connects to is baked into it
+fn write_project(out_dir: &Path) -> std::path::PathBuf {
+    let port = match std::env::var("RBX_MCP_PORT") {
+        Ok(value) => value
+            .parse::<u16>()
+            .ok()
+            .filter(|port| *port != 0)
+            .unwrap_or_else(|| panic!("RBX_MCP_PORT must be a port number, got {value:?}")),
+        Err(_) => DEFAULT_PLUGIN_PORT,
+    };
+    let port_module = out_dir.join("PluginPort.luau");
+    std::fs::write(&port_module, format!("return {port}\n")).unwrap();
+    let host_module = out_dir.join("PluginHost.luau");
+    std::fs::write(&host_module, format!("return {:?}\n", plugin_host())).unwrap();
+
+    let sources = Path::new(&std::env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("plugin/src");
+    let project = format!(
+        r#"{{
+  "name": "MCPStudioPlugin",
+  "tree": {{
+    "$path": {},
+    "PluginPort": {{ "$path": {} }},
+    "PluginHost": {{ "$path": {} }}
+  }}
+}}
+"#,
+        json_string(&sources),
+        json_string(&port_module),
+        json_string(&host_module)
+    );
+    let project_path = out_dir.join("plugin.project.json");
+    std::fs::write(&project_path, project).unwrap();
+    project_path
+}
 
 fn main() {
     let out_dir = std::env::var_os("OUT_DIR").unwrap();
@@ -10,7 +74,7 @@ fn main() {
             color: cli::ColorChoice::Always,
         },
         subcommand: cli::Subcommand::Build(cli::BuildCommand {
-            project: std::path::PathBuf::from("plugin"),
+            project: write_project(Path::new(&out_dir)),
             output: Some(dest_path),
             plugin: None,
             watch: false,
@@ -18,4 +82,6 @@ fn main() {
     };
     options.run().unwrap();
     println!("cargo:rerun-if-changed=plugin");
+    println!("cargo:rerun-if-env-changed=RBX_MCP_PORT");
+    println!("cargo:rerun-if-env-changed=RBX_MCP_PLUGIN_HOST");
 }
@@ -0,0 +1,88 @@
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// Runtime configuration shared by the MCP tools
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    /// Directory file-based tools may read from and write to, file tools are disabled when unset
+    pub files_dir: Option<PathBuf>,
+    /// Only expose tools annotated as read-only
+    pub read_only: bool,
+    /// Tools removed from the tool list by name
+    pub disabled_tools: Vec<String>,
+    /// File logs are written to in addition to stderr
+    pub log_file: Option<PathBuf>,
+    /// Port of the HTTP server the Studio plugin polls
+    pub port: u16,
+    /// Address the plugin HTTP server binds to
+    pub host: IpAddr,
+    /// How long to wait for the plugin to answer a command, `None` waits forever
+    pub command_timeout: Option<Duration>,
+    /// Commands fail right away when the plugin hasn't polled for this long, `None` always queues them
+    pub connection_timeout: Option<Duration>,
+    /// How long a poll from the plugin is held open when no command is queued
+    pub poll_duration: Duration,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            files_dir: None,
+            read_only: false,
+            disabled_tools: Vec::new(),
+            log_file: None,
+            port: crate::rbx_studio_server::STUDIO_PLUGIN_PORT,
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            command_timeout: Some(Duration::from_secs(30)),
+            connection_timeout: Some(Duration::from_secs(30)),
+            poll_duration: Duration::from_secs(15),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Resolves `relative` inside the files directory, rejecting paths that would escape it
+    pub fn files_path(&self, relative: &str) -> Result<PathBuf> {
+        let base = self.files_dir.as_ref().ok_or_else(|| {
+            eyre!("File tools are disabled, start the server with --files-dir or RBX_MCP_FILES_DIR set")
+        })?;
+        let relative = Path::new(relative);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(eyre!(
+                "Path {} must be relative to the files directory and must not contain ..",
+                relative.display()
+            ));
+        }
+
+        let base = base
+            .canonicalize()
+            .wrap_err_with(|| format!("Files directory {} is not accessible", base.display()))?;
+        let path = base.join(relative);
+        if !resolves_inside(&base, &path) {
+            return Err(eyre!(
+                "Path {} resolves outside the files directory",
+                relative.display()
+            ));
+        }
+        Ok(path)
+    }
+}
+
+/// Whether `path` stays inside the canonical directory `base` once symlinks are followed. Paths that don't exist yet
+/// are checked through their deepest existing ancestor, since that is where new files and directories would end up.
+pub fn resolves_inside(base: &Path, path: &Path) -> bool {
+    for ancestor in path.ancestors() {
+        match ancestor.canonicalize() {
+            Ok(resolved) => return resolved.starts_with(base),
+            // A dangling symlink would be followed when writing through it
+            Err(_) if ancestor.symlink_metadata().is_ok() => return false,
+            Err(_) => {}
+        }
+    }
+    false
+}
@@ -1,9 +1,11 @@
+use crate::rbx_studio_server::RBXStudioServer;
+use crate::script_diff::unified_diff;
 use color_eyre::eyre::{eyre, Result, WrapErr};
 use color_eyre::Help;
 use roblox_install::RobloxStudio;
+use serde::Serialize;
 use serde_json::{json, Value};
 use std::fs::File;
-use std::io::BufReader;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
@@ -11,22 +13,80 @@ use std::vec;
 use std::{env, fs, io};
 
 fn get_message(successes: String) -> String {
+    let tools = RBXStudioServer::tool_names()
+        .iter()
+        .map(|name| format!("- {name}"))
+        .collect::<Vec<_>>()
+        .join("\n");
     format!("Roblox Studio MCP is ready to go.
 Please restart Studio and MCP clients to apply the changes.
 
 Tools included:
-- run_code
-- insert_model
-- get_console_output
-- start_stop_play
-- run_script_in_play_mode
-- get_studio_mode
+{tools}
 
 MCP Clients set up:
 {successes}
 
 Note: connecting a third-party LLM to Roblox Studio via an MCP server will share your data with that external service provider. Please review their privacy practices carefully before proceeding.
-To uninstall, delete the MCPStudioPlugin.rbxm from your Plugins directory.")
+To uninstall, run this program with --uninstall.")
+}
+
+/// MCP clients the installer can set up
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Client {
+    Claude,
+    Cursor,
+    Antigravity,
+    VsCode,
+    /// Only gets the command to add the server, Claude Code's config is managed by its CLI
+    ClaudeCode,
+}
+
+impl Client {
+    const ALL: [Client; 5] = [
+        Client::Claude,
+        Client::Cursor,
+        Client::Antigravity,
+        Client::VsCode,
+        Client::ClaudeCode,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Client::Claude => "Claude",
+            Client::Cursor => "Cursor",
+            Client::Antigravity => "Antigravity",
+            Client::VsCode => "VS Code",
+            Client::ClaudeCode => "Claude Code",
+        }
+    }
+
+    fn config_paths(self) -> Vec<PathBuf> {
+        match self {
+            Client::Claude => get_claude_config(),
+            Client::Cursor => get_cursor_config(),
+            Client::Antigravity => get_antigravity_config(),
+            Client::VsCode => get_vscode_config(),
+            Client::ClaudeCode => vec![],
+        }
+    }
+
+    /// Key of the object servers are listed under
+    fn servers_key(self) -> &'static str {
+        match self {
+            Client::VsCode => "servers",
+            _ => "mcpServers",
+        }
+    }
+
+    /// The chosen clients, or every client when none were chosen
+    fn selected(clients: &[Client]) -> &[Client] {
+        if clients.is_empty() {
+            &Self::ALL
+        } else {
+            clients
+        }
+    }
 }
 
 // returns OS dependant claude_desktop_config.json path
@@ -80,6 +140,13 @@ fn get_cursor_config() -> Vec<PathBuf> {
     if let Some(home_dir) = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
         paths.push(Path::new(&home_dir).join(".cursor").join("mcp.json"));
     }
+    // Project config, set up when the installer runs from a project with a .cursor directory
+    if let Ok(current_dir) = env::current_dir() {
+        let project = current_dir.join(".cursor").join("mcp.json");
+        if !paths.contains(&project) {
+            paths.push(project);
+        }
+    }
     paths
 }
 
@@ -96,6 +163,26 @@ fn get_antigravity_config() -> Vec<PathBuf> {
     paths
 }
 
+// returns the user mcp.json of VS Code and VS Code Insiders
+fn get_vscode_config() -> Vec<PathBuf> {
+    let config_dir = if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| Path::new(&home).join("Library").join("Application Support"))
+    } else if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+    let Some(config_dir) = config_dir else {
+        return vec![];
+    };
+    ["Code", "Code - Insiders"]
+        .iter()
+        .map(|app| config_dir.join(app).join("User").join("mcp.json"))
+        .collect()
+}
+
 #[cfg(target_os = "macos")]
 fn get_exe_path() -> Result<PathBuf> {
     use core_foundation::url::CFURL;
@@ -113,24 +200,136 @@ fn get_exe_path() -> io::Result<PathBuf> {
     env::current_exe()
 }
 
-pub fn suggest_to_config_claude_code(exe_path: &Path) -> Result<String> {
-    let home_dir = env::var_os("HOME")
-        .or_else(|| env::var_os("USERPROFILE"))
-        .unwrap();
-    let config_path = Path::new(&home_dir).join(".claude.json");
+fn get_claude_code_config() -> Option<PathBuf> {
+    let home_dir = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+    Some(Path::new(&home_dir).join(".claude.json")).filter(|path| path.exists())
+}
 
-    if config_path.exists() {
+pub fn suggest_to_config_claude_code(exe_path: &Path) -> Result<String> {
+    if get_claude_code_config().is_some() {
         Ok(format!("To add the MCP to Claude Code CLI run:\nclaude mcp add --transport stdio Roblox_Studio -- '{}' --stdio", exe_path.display()))
     } else {
         Err(eyre!("No config file found"))
     }
 }
 
-pub fn install_to_config(
-    config_paths: Vec<PathBuf>,
-    exe_path: &Path,
+/// Reads a client config. Files that aren't JSON with an object under `servers_key` are backed up and start over empty,
+/// returning the contents to keep the file's formatting when it's written back.
+fn read_config(
+    config_path: &Path,
+    name: &str,
+    servers_key: &str,
+    dry_run: bool,
+) -> Result<(serde_json::Map<String, Value>, String)> {
+    let contents = match fs::read_to_string(config_path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Default::default()),
+        Err(err) => {
+            return Err(eyre!(
+                "Could not read {name} config file at {config_path:?}: {err}"
+            ))
+        }
+    };
+    if contents.trim().is_empty() {
+        return Ok((serde_json::Map::new(), contents));
+    }
+
+    let problem = match serde_json::from_str::<serde_json::Map<String, Value>>(&contents) {
+        Ok(config) if matches!(config.get(servers_key), None | Some(Value::Object(_))) => {
+            return Ok((config, contents));
+        }
+        Ok(_) => format!("its {servers_key} isn't an object"),
+        Err(err) => format!("it isn't valid JSON: {err}"),
+    };
+    let backup = backup_path(config_path);
+    if dry_run {
+        eprintln!(
+            "Would back up {name} config file {config_path:?} to {backup:?} before replacing it, {problem}"
+        );
+        return Ok((serde_json::Map::new(), contents));
+    }
+    fs::copy(config_path, &backup)
+        .wrap_err_with(|| format!("Could not back up {name} config file to {backup:?}"))?;
+    eprintln!(
+        "Backed up {name} config file {config_path:?} to {backup:?} before replacing it, {problem}"
+    );
+    Ok((serde_json::Map::new(), contents))
+}
+
+/// First of `config.json.bak`, `config.json.bak1`, ... that doesn't exist yet
+fn backup_path(config_path: &Path) -> PathBuf {
+    (0..)
+        .map(|n| {
+            let mut path = config_path.as_os_str().to_owned();
+            path.push(if n == 0 {
+                ".bak".to_string()
+            } else {
+                format!(".bak{n}")
+            });
+            PathBuf::from(path)
+        })
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+/// Writes a config back with the indentation and trailing newline of what was read. A dry run prints the diff it
+/// would apply instead.
+fn write_config(
+    config_path: &Path,
+    config: &serde_json::Map<String, Value>,
+    original: &str,
     name: &str,
-) -> Result<String> {
+    dry_run: bool,
+) -> Result<()> {
+    let indent = original
+        .lines()
+        .map(|line| &line[..line.len() - line.trim_start_matches([' ', '\t']).len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("  ");
+    let mut contents = vec![];
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    config.serialize(&mut serde_json::Serializer::with_formatter(
+        &mut contents,
+        formatter,
+    ))?;
+    if original.ends_with('\n') {
+        contents.push(b'\n');
+    }
+    if dry_run {
+        let path = config_path.display().to_string();
+        let diff = unified_diff(&path, original, &path, &String::from_utf8(contents)?);
+        eprintln!("Would change {name} config {config_path:?}:\n{diff}");
+        return Ok(());
+    }
+    fs::write(config_path, contents)
+        .map_err(|e| eyre!("Could not write to {name} config file at {config_path:?}: {e:#?}"))
+}
+
+/// Points the server entry at this executable. Options users added, such as extra args or env, are kept.
+fn update_server_entry(entry: &mut Value, exe_path: &Path, client: Client) {
+    if !entry.is_object() {
+        *entry = json!({});
+    }
+    // VS Code names the transport of each server
+    if client == Client::VsCode && entry.get("type").is_none() {
+        entry["type"] = json!("stdio");
+    }
+    entry["command"] = json!(exe_path);
+    match entry.get_mut("args").and_then(Value::as_array_mut) {
+        Some(args) => {
+            if !args.iter().any(|arg| arg == "--stdio") {
+                args.insert(0, json!("--stdio"));
+            }
+        }
+        None => entry["args"] = json!(["--stdio"]),
+    }
+}
+
+/// Adds or updates the server in a client's config files, leaving every other entry as it was. Files that are
+/// already up to date aren't rewritten.
+pub fn install_to_config(client: Client, exe_path: &Path, dry_run: bool) -> Result<String> {
+    let (config_paths, name, servers_key) =
+        (client.config_paths(), client.name(), client.servers_key());
     if config_paths.is_empty() {
         return Err(eyre!("No config paths found for {name}"));
     }
@@ -146,52 +345,143 @@ pub fn install_to_config(
     }
 
     for config_path in &valid_paths {
-        let mut config: serde_json::Map<String, Value> = {
-            if !config_path.exists() {
-                let mut file = File::create(config_path).map_err(|e| {
-                    eyre!("Could not create {name} config file at {config_path:?}: {e:#?}")
-                })?;
-                file.write_all(serde_json::to_string(&serde_json::Map::new())?.as_bytes())?;
-            }
-            let config_file = File::open(config_path).map_err(|error| {
-                eyre!("Could not read or create {name} config file: {error:#?}")
+        let (mut config, original) = read_config(config_path, name, servers_key, dry_run)?;
+        let before = config.clone();
+
+        let mcp_servers = config
+            .entry(servers_key)
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("read_config only returns object servers");
+        // Remove old key if it exists
+        mcp_servers.shift_remove("Roblox Studio");
+        update_server_entry(
+            mcp_servers
+                .entry("Roblox_Studio")
+                .or_insert_with(|| json!({})),
+            exe_path,
+            client,
+        );
+
+        if config == before && !original.is_empty() {
+            eprintln!("MCP Studio plugin is already set up in {name} config {config_path:?}");
+            continue;
+        }
+        write_config(config_path, &config, &original, name, dry_run)?;
+        if !dry_run {
+            eprintln!("Installed MCP Studio plugin to {name} config {config_path:?}");
+        }
+    }
+
+    Ok(name.to_string())
+}
+
+/// Removes the server from a client's config files, keeping every other server. Returns what was removed.
+fn uninstall_from_config(client: Client, dry_run: bool) -> Result<Vec<String>> {
+    let name = client.name();
+    let mut removed = vec![];
+    for config_path in client.config_paths().into_iter().filter(|p| p.exists()) {
+        let original = fs::read_to_string(&config_path)
+            .wrap_err_with(|| format!("Could not read {name} config file at {config_path:?}"))?;
+        let mut config: serde_json::Map<String, Value> = serde_json::from_str(&original)
+            .wrap_err_with(|| {
+                format!("{name} config file at {config_path:?} isn't valid JSON, leaving it alone")
             })?;
-            let reader = BufReader::new(config_file);
-            serde_json::from_reader(reader).unwrap_or(serde_json::Map::new())
+
+        let Some(Value::Object(mcp_servers)) = config.get_mut(client.servers_key()) else {
+            continue;
         };
+        // Older versions installed the server under "Roblox Studio"
+        let mut found = false;
+        for key in ["Roblox_Studio", "Roblox Studio"] {
+            found |= mcp_servers.shift_remove(key).is_some();
+        }
+        if !found {
+            continue;
+        }
 
-        if !matches!(config.get("mcpServers"), Some(Value::Object(_))) {
-            config.insert("mcpServers".to_string(), json!({}));
+        write_config(&config_path, &config, &original, name, dry_run)?;
+        if !dry_run {
+            removed.push(format!(
+                "Removed MCP server from {name} config {config_path:?}"
+            ));
         }
+    }
+    Ok(removed)
+}
 
-        // Remove old key if it exists
-        if let Some(Value::Object(mcp_servers)) = config.get_mut("mcpServers") {
-            mcp_servers.remove("Roblox Studio");
+/// Reverses the installer for the chosen clients, or all of them, safe to run again when nothing is installed. A dry
+/// run only prints what would be removed.
+pub fn uninstall(clients: &[Client], dry_run: bool) -> Result<()> {
+    let mut removed = vec![];
+    let mut errors = vec![];
+
+    match RobloxStudio::locate() {
+        Ok(studio) => {
+            let plugin = studio.plugins_path().join("MCPStudioPlugin.rbxm");
+            if dry_run {
+                if plugin.exists() {
+                    eprintln!("Would remove Roblox Studio plugin {}", plugin.display());
+                }
+            } else {
+                match fs::remove_file(&plugin) {
+                    Ok(()) => {
+                        removed.push(format!("Removed Roblox Studio plugin {}", plugin.display()))
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => errors.push(eyre!(
+                        "Could not remove Roblox Studio plugin {}: {err}",
+                        plugin.display()
+                    )),
+                }
+            }
         }
+        // Without Studio there's no plugin to remove
+        Err(err) => eprintln!("Skipping the Studio plugin, could not locate Roblox Studio: {err}"),
+    }
 
-        config["mcpServers"]["Roblox_Studio"] = json!({
-          "command": &exe_path,
-          "args": [
-            "--stdio"
-          ]
-        });
-
-        let mut file = File::create(config_path)?;
-        file.write_all(serde_json::to_string_pretty(&config)?.as_bytes())
-            .map_err(|e| {
-                eyre!("Could not write to {name} config file at {config_path:?}: {e:#?}")
-            })?;
+    let clients = Client::selected(clients);
+    for client in clients {
+        match uninstall_from_config(*client, dry_run) {
+            Ok(lines) => removed.extend(lines),
+            Err(err) => errors.push(err),
+        }
+    }
 
-        println!("Installed MCP Studio plugin to {name} config {config_path:?}");
+    if dry_run {
+        eprintln!("Dry run, nothing was changed");
+    } else if removed.is_empty() {
+        eprintln!("Nothing to remove, Roblox Studio MCP isn't installed");
+    }
+    for line in &removed {
+        eprintln!("{line}");
+    }
+    // Claude Code keeps its servers in its own config, only its CLI should change it
+    if clients.contains(&Client::ClaudeCode) && get_claude_code_config().is_some() {
+        eprintln!("To remove the MCP from Claude Code CLI run:\nclaude mcp remove Roblox_Studio");
     }
 
-    Ok(name.to_string())
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.into_iter().fold(
+            eyre!("Some parts of Roblox Studio MCP could not be removed"),
+            |report, e| report.note(e),
+        ))
+    }
 }
 
-async fn install_internal() -> Result<String> {
+fn install_plugin(dry_run: bool) -> Result<()> {
     let plugin_bytes = include_bytes!(concat!(env!("OUT_DIR"), "/MCPStudioPlugin.rbxm"));
     let studio = RobloxStudio::locate()?;
     let plugins = studio.plugins_path();
+    if dry_run {
+        eprintln!(
+            "Would install Roblox Studio plugin to {}",
+            plugins.join("MCPStudioPlugin.rbxm").display()
+        );
+        return Ok(());
+    }
     if let Err(err) = fs::create_dir(plugins) {
         if err.kind() != io::ErrorKind::AlreadyExists {
             return Err(err.into());
@@ -207,54 +497,73 @@ async fn install_internal() -> Result<String> {
         })?;
         file.write_all(plugin_bytes)?;
     }
-    println!(
+    eprintln!(
         "Installed Roblox Studio plugin to {}",
         output_plugin.display()
     );
+    Ok(())
+}
+
+async fn install_internal(clients: &[Client], dry_run: bool) -> Result<String> {
+    // Clients are still set up when the plugin can't be installed, the error is reported once they are
+    let plugin = install_plugin(dry_run).wrap_err("Could not install the Roblox Studio plugin");
 
     let this_exe = get_exe_path()?;
 
     let mut errors = vec![];
-    let results = vec![
-        install_to_config(get_claude_config(), &this_exe, "Claude"),
-        install_to_config(get_cursor_config(), &this_exe, "Cursor"),
-        install_to_config(get_antigravity_config(), &this_exe, "Antigravity"),
-        suggest_to_config_claude_code(&this_exe),
-    ];
+    let results: Vec<_> = Client::selected(clients)
+        .iter()
+        .map(|client| match client {
+            Client::ClaudeCode => suggest_to_config_claude_code(&this_exe),
+            client => install_to_config(*client, &this_exe, dry_run),
+        })
+        .collect();
 
     let successes: Vec<_> = results
         .into_iter()
         .filter_map(|r| r.map_err(|e| errors.push(e)).ok())
         .collect();
 
-    if successes.is_empty() {
-        let error = errors.into_iter().fold(
+    // Clients chosen with --client have to be set up, detected ones are skipped when they aren't installed
+    if successes.is_empty() || (!clients.is_empty() && !errors.is_empty()) {
+        let error = plugin.err().into_iter().chain(errors).fold(
             eyre!("\nFailed to automatically set up, please use manual instructions.\n"),
             |report, e| report.note(e),
         );
         return Err(error);
     }
+    plugin?;
 
-    println!();
-    let msg = get_message(successes.join("\n"));
-    println!("{msg}");
+    eprintln!();
+    let msg = if dry_run {
+        "Dry run, nothing was changed. Run again without --dry-run to apply these changes."
+            .to_string()
+    } else {
+        get_message(successes.join("\n"))
+    };
+    eprintln!("{msg}");
     Ok(msg)
 }
 
 #[cfg(target_os = "windows")]
-pub async fn install() -> Result<()> {
+pub async fn install(clients: &[Client], dry_run: bool) -> Result<()> {
     use std::process::Command;
-    if let Err(e) = install_internal().await {
+    if let Err(e) = install_internal(clients, dry_run).await {
         tracing::error!("Failed initialize Roblox MCP: {:#}", e);
     }
-    let _ = Command::new("cmd.exe").arg("/c").arg("pause").status();
+    // Keep stdout free of anything that isn't a JSON-RPC frame, even in the installer
+    let _ = Command::new("cmd.exe")
+        .arg("/c")
+        .arg("pause")
+        .stdout(io::stderr())
+        .status();
     Ok(())
 }
 
 #[cfg(target_os = "macos")]
-pub async fn install() -> Result<()> {
+pub async fn install(clients: &[Client], dry_run: bool) -> Result<()> {
     use native_dialog::{DialogBuilder, MessageLevel};
-    let alert_builder = match install_internal().await {
+    let alert_builder = match install_internal(clients, dry_run).await {
         Err(e) => DialogBuilder::message()
             .set_level(MessageLevel::Error)
             .set_text(format!("Errors occurred: {e:#}")),
@@ -267,7 +576,7 @@ pub async fn install() -> Result<()> {
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub async fn install() -> Result<()> {
-    install_internal().await?;
+pub async fn install(clients: &[Client], dry_run: bool) -> Result<()> {
+    install_internal(clients, dry_run).await?;
     Ok(())
 }
@@ -0,0 +1,254 @@
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Resolves instance paths the way the plugin's InstancePath does, emitted once when a step needs it
+const RESOLVE_HELPER: &str = r#"local function resolve(path: string): Instance
+	local current: Instance = game
+	for index, name in string.split(path, ".") do
+		if index == 1 and (name == "game" or name == "Game") then
+			continue
+		end
+		local child = current:FindFirstChild(name)
+		if child == nil and current == game then
+			child = game:GetService(name)
+		end
+		current = child or error(`Could not resolve path {path}`)
+	end
+	return current
+end
+"#;
+
+/// Builds a standalone Luau script out of recorded tool calls, one commented block per step
+pub struct ScriptBuilder {
+    title: String,
+    body: String,
+    needs_resolve: bool,
+    omitted: usize,
+}
+
+/// Tool name as the client sees it, e.g. `SetProperty` becomes `set_property`
+pub fn tool_name(variant: &str) -> String {
+    let mut name = String::new();
+    for (index, char) in variant.chars().enumerate() {
+        if char.is_uppercase() && index > 0 {
+            name.push('_');
+        }
+        name.push(char.to_ascii_lowercase());
+    }
+    name
+}
+
+/// Quotes a string as a Luau string literal
+pub fn string_literal(value: &str) -> String {
+    let mut literal = String::from('"');
+    for char in value.chars() {
+        match char {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            char if char.is_control() => {
+                let _ = write!(literal, "\\u{{{:x}}}", char as u32);
+            }
+            char => literal.push(char),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+fn numbers(value: &Value, count: usize) -> Option<String> {
+    let items = value.as_array().filter(|items| items.len() == count)?;
+    let numbers = items
+        .iter()
+        .map(|item| item.as_f64().map(|n| n.to_string()))
+        .collect::<Option<Vec<_>>>()?;
+    Some(numbers.join(", "))
+}
+
+/// Writes a `{type, value}` encoded datatype as the constructor call the plugin's ValueCodec would decode it to
+fn datatype_literal(value_type: &str, value: &Value) -> Option<String> {
+    let literal = match value_type {
+        "number" => match value.as_str()? {
+            "nan" => "0 / 0".to_string(),
+            "inf" => "math.huge".to_string(),
+            "-inf" => "-math.huge".to_string(),
+            _ => return None,
+        },
+        "Vector2" | "Vector3" | "Color3" | "UDim" | "UDim2" | "Rect" | "NumberRange" => {
+            let count = match value_type {
+                "Vector2" | "UDim" | "NumberRange" => 2,
+                "Vector3" | "Color3" => 3,
+                _ => 4,
+            };
+            format!("{value_type}.new({})", numbers(value, count)?)
+        }
+        "CFrame" => format!("CFrame.new({})", numbers(value, 3).or(numbers(value, 12))?),
+        "NumberSequence" => {
+            let keypoints = value
+                .as_array()?
+                .iter()
+                .map(|keypoint| {
+                    let keypoint = keypoint.as_array()?;
+                    let envelope = keypoint.get(2).and_then(Value::as_f64).unwrap_or(0.0);
+                    Some(format!(
+                        "NumberSequenceKeypoint.new({}, {}, {envelope})",
+                        keypoint.first()?.as_f64()?,
+                        keypoint.get(1)?.as_f64()?
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            format!("NumberSequence.new({{ {} }})", keypoints.join(", "))
+        }
+        "ColorSequence" => {
+            let keypoints = value
+                .as_array()?
+                .iter()
+                .map(|keypoint| {
+                    let time = keypoint.get(0)?.as_f64()?;
+                    let color = numbers(&Value::from(keypoint.as_array()?.get(1..)?), 3)?;
+                    Some(format!(
+                        "ColorSequenceKeypoint.new({time}, Color3.new({color}))"
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            format!("ColorSequence.new({{ {} }})", keypoints.join(", "))
+        }
+        "BrickColor" => format!("BrickColor.new({})", string_literal(value.as_str()?)),
+        "EnumItem" => {
+            let item = value.as_str()?;
+            let item = item.strip_prefix("Enum.").unwrap_or(item);
+            let (enum_name, item_name) = item.split_once('.')?;
+            format!("Enum.{enum_name}.{item_name}")
+        }
+        "Font" => {
+            let weight = value["weight"].as_str().unwrap_or("Regular");
+            let style = value["style"].as_str().unwrap_or("Normal");
+            format!(
+                "Font.new({}, Enum.FontWeight.{weight}, Enum.FontStyle.{style})",
+                string_literal(value["family"].as_str()?)
+            )
+        }
+        "Instance" => format!("resolve({})", string_literal(value.as_str()?)),
+        _ => return None,
+    };
+    Some(literal)
+}
+
+impl ScriptBuilder {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            body: String::new(),
+            needs_resolve: false,
+            omitted: 0,
+        }
+    }
+
+    /// Writes a JSON value as a Luau expression, decoding `{type, value}` datatypes like the plugin does. Returns
+    /// `None` for datatypes that can't be written as a literal.
+    pub fn literal(&mut self, value: &Value) -> Option<String> {
+        let literal = match value {
+            Value::Null => "nil".to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Number(value) => value.to_string(),
+            Value::String(value) => string_literal(value),
+            Value::Array(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.literal(item))
+                    .collect::<Option<Vec<_>>>()?;
+                format!("{{ {} }}", items.join(", "))
+            }
+            Value::Object(fields) => {
+                if let (Some(Value::String(value_type)), Some(value)) =
+                    (fields.get("type"), fields.get("value"))
+                {
+                    self.needs_resolve |= value_type == "Instance";
+                    return datatype_literal(value_type, value);
+                }
+                let fields = fields
+                    .iter()
+                    .map(|(key, item)| {
+                        Some(format!(
+                            "[{}] = {}",
+                            string_literal(key),
+                            self.literal(item)?
+                        ))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                format!("{{ {} }}", fields.join(", "))
+            }
+        };
+        Some(literal)
+    }
+
+    /// Writes a value given to a property. Untagged arrays and objects are decoded by the plugin according to the
+    /// property's type, which isn't known here, so they give `None`. Strings stay strings, which Roblox also accepts
+    /// for enum properties.
+    pub fn property_value(&mut self, value: &Value) -> Option<String> {
+        match value {
+            Value::Array(_) => None,
+            Value::Object(fields) if !fields.contains_key("type") => None,
+            value => self.literal(value),
+        }
+    }
+
+    /// Expression for the instance at `path`
+    pub fn instance(&mut self, path: &str) -> String {
+        self.needs_resolve = true;
+        format!("resolve({})", string_literal(path))
+    }
+
+    /// Adds code that ran through run_code. It runs inside its own function so its locals and early returns stay
+    /// within the step, with the payload passed as `args` like the plugin does.
+    pub fn code(&mut self, step: usize, tool: &str, command: &str, args: Option<&Value>) {
+        let args = match args.map(|args| self.literal(args)) {
+            Some(Some(args)) => args,
+            Some(None) => {
+                self.omit(
+                    step,
+                    tool,
+                    "its args hold a value that can't be written as Luau",
+                );
+                return;
+            }
+            None => "nil".to_string(),
+        };
+        let _ = write!(
+            self.body,
+            "\n-- Step {step}: {tool}\ndo\n\tlocal function run(args)\n{}\n\tend\n\trun({args})\nend\n",
+            command.trim_end()
+        );
+    }
+
+    /// Adds statements for a step, each on its own line inside a `do` block
+    pub fn statements(&mut self, step: usize, tool: &str, statements: &[String]) {
+        let _ = write!(self.body, "\n-- Step {step}: {tool}\ndo\n");
+        for statement in statements {
+            let _ = writeln!(self.body, "\t{statement}");
+        }
+        self.body.push_str("end\n");
+    }
+
+    /// Records a step that isn't reproduced, with the reason as a comment
+    pub fn omit(&mut self, step: usize, tool: &str, reason: &str) {
+        self.omitted += 1;
+        let _ = write!(self.body, "\n-- Step {step}: {tool} omitted, {reason}\n");
+    }
+
+    pub fn finish(self, steps: usize) -> String {
+        let mut source = format!(
+            "-- {}\n-- Generated by the Roblox Studio MCP server from {steps} steps, {} of them omitted. Run it in the \
+             command bar or a plugin.\n",
+            self.title, self.omitted
+        );
+        if self.needs_resolve {
+            source.push('\n');
+            source.push_str(RESOLVE_HELPER);
+        }
+        source.push_str(&self.body);
+        source
+    }
+}
@@ -1,16 +1,44 @@
-use axum::routing::{get, post};
-use clap::Parser;
-use color_eyre::eyre::Result;
+use axum::extract::DefaultBodyLimit;
+use axum::routing::{any, get, post};
+use clap::{Parser, ValueEnum};
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use config::AppConfig;
 use rbx_studio_server::*;
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
 use rmcp::ServiceExt;
+use std::fs::File;
 use std::io;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
-use tracing_subscriber::{self, EnvFilter};
+use tracing_subscriber::{self, fmt::writer::MakeWriterExt, EnvFilter};
+mod config;
 mod error;
 mod install;
+mod luau_export;
 mod rbx_studio_server;
+mod screenshot;
+mod script_diff;
+mod script_files;
+mod selector;
+
+/// Polls held open longer than this are warned about
+const MAX_SENSIBLE_POLL_SECS: u64 = 300;
+
+/// Port MCP clients connect to with `--transport http`, next to the plugin port
+const MCP_HTTP_PORT: u16 = 44756;
+
+/// How MCP clients reach the server
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Transport {
+    /// Over stdin and stdout of a process the client launches
+    Stdio,
+    /// Over streamable HTTP at /mcp, for clients connecting to a running server
+    Http,
+}
 
 /// Simple MCP proxy for Roblox Studio
 /// Run without arguments to install the plugin
@@ -20,31 +48,191 @@ struct Args {
     /// Run as MCP server on stdio
     #[arg(short, long)]
     stdio: bool,
+
+    /// Transport MCP clients use, http runs the server without --stdio
+    #[arg(long, env = "RBX_MCP_TRANSPORT", value_enum, default_value_t = Transport::Stdio)]
+    transport: Transport,
+
+    /// Port MCP clients connect to with --transport http, must differ from the plugin port
+    #[arg(
+        long,
+        env = "RBX_MCP_HTTP_PORT",
+        default_value_t = MCP_HTTP_PORT,
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    mcp_port: u16,
+
+    /// Remove the Studio plugin and the MCP client config entries the installer added
+    #[arg(long)]
+    uninstall: bool,
+
+    /// Print the files the installer or --uninstall would change and the diff of each, without changing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// MCP client to install to or uninstall from, can be repeated. Every detected client by default
+    #[arg(long = "client", value_name = "CLIENT", value_enum)]
+    clients: Vec<install::Client>,
+
+    /// Directory that file-based tools may read from and write to
+    #[arg(long, env = "RBX_MCP_FILES_DIR")]
+    files_dir: Option<PathBuf>,
+
+    /// Only expose tools that don't modify the place
+    #[arg(long, env = "RBX_MCP_READ_ONLY")]
+    read_only: bool,
+
+    /// Tool to hide from MCP clients, can be repeated or comma separated
+    #[arg(
+        long = "disable-tool",
+        value_name = "TOOL",
+        env = "RBX_MCP_DISABLED_TOOLS",
+        value_delimiter = ','
+    )]
+    disabled_tools: Vec<String>,
+
+    /// Port the Studio plugin connects to, the plugin uses the RBX_MCP_PORT it was built with
+    #[arg(
+        long,
+        env = "RBX_MCP_PORT",
+        default_value_t = STUDIO_PLUGIN_PORT,
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    port: u16,
+
+    /// Address the plugin HTTP server binds to, use 0.0.0.0 when Studio runs in another VM or container. The plugin
+    /// connects to the RBX_MCP_PLUGIN_HOST it was built with, localhost by default
+    #[arg(long, env = "RBX_MCP_HOST", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    host: IpAddr,
+
+    /// Seconds to wait for Studio to answer a command before failing the tool call, 0 waits forever
+    #[arg(
+        long,
+        value_name = "SECS",
+        env = "RBX_MCP_COMMAND_TIMEOUT",
+        default_value_t = 30
+    )]
+    command_timeout: u64,
+
+    /// Seconds without a poll from the plugin after which tool calls fail right away, 0 always waits for Studio
+    #[arg(
+        long,
+        value_name = "SECS",
+        env = "RBX_MCP_CONNECTION_TIMEOUT",
+        default_value_t = 30
+    )]
+    connection_timeout: u64,
+
+    /// Seconds a poll from the plugin is held open waiting for a command before it is answered empty
+    #[arg(
+        long,
+        value_name = "SECS",
+        env = "RBX_MCP_POLL_SECS",
+        default_value_t = 15,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    poll_secs: u64,
+
+    /// File to also write logs to while serving, rbx-studio-mcp.log in the temp directory when unset
+    #[arg(long, value_name = "PATH", env = "RBX_MCP_LOG_FILE")]
+    log_file: Option<PathBuf>,
+}
+
+/// Log file servers append to when --log-file isn't given, shared by every instance on the machine
+fn default_log_file() -> PathBuf {
+    std::env::temp_dir().join("rbx-studio-mcp.log")
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
-    tracing_subscriber::fmt()
+    let args = Args::parse();
+
+    // Installing and uninstalling only log to stderr unless asked otherwise
+    let serving = args.stdio || args.transport == Transport::Http;
+    let log_path = args
+        .log_file
+        .clone()
+        .or_else(|| serving.then(default_log_file));
+    // A log file that can't be created shouldn't keep the server from starting
+    let opened = log_path.as_ref().map(|path| match args.log_file {
+        Some(_) => File::create(path),
+        None => File::options().create(true).append(true).open(path),
+    });
+    let (log_file, log_file_error) = match opened {
+        Some(Ok(file)) => (Some(file), None),
+        Some(Err(err)) => (None, Some(err)),
+        None => (None, None),
+    };
+    let subscriber = tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
-        .with_writer(io::stderr)
         .with_target(false)
-        .with_thread_ids(true)
-        .init();
+        .with_thread_ids(true);
+    if let Some(file) = log_file {
+        subscriber
+            .with_ansi(false)
+            .with_writer(io::stderr.and(std::sync::Mutex::new(file)))
+            .init();
+    } else {
+        subscriber.with_writer(io::stderr).init();
+    }
+    if let (Some(path), Some(err)) = (&log_path, &log_file_error) {
+        tracing::warn!(
+            "Unable to create log file {}, logging to stderr only: {err}",
+            path.display()
+        );
+    }
 
-    let args = Args::parse();
-    if !args.stdio {
-        return install::install().await;
+    if args.uninstall {
+        return install::uninstall(&args.clients, args.dry_run);
+    }
+    if !args.stdio && args.transport == Transport::Stdio {
+        return install::install(&args.clients, args.dry_run).await;
+    }
+    if args.transport == Transport::Http && args.mcp_port == args.port {
+        return Err(eyre!(
+            "--mcp-port and --port are both {}, the MCP endpoint needs a port of its own",
+            args.port
+        ));
     }
 
     tracing::debug!("Debug MCP tracing enabled");
 
-    let server_state = Arc::new(Mutex::new(AppState::new()));
+    let config = AppConfig {
+        files_dir: args.files_dir,
+        read_only: args.read_only,
+        disabled_tools: args.disabled_tools,
+        log_file: log_path.filter(|_| log_file_error.is_none()),
+        port: args.port,
+        host: args.host,
+        command_timeout: Some(Duration::from_secs(args.command_timeout))
+            .filter(|timeout| !timeout.is_zero()),
+        connection_timeout: Some(Duration::from_secs(args.connection_timeout))
+            .filter(|window| !window.is_zero()),
+        poll_duration: Duration::from_secs(args.poll_secs),
+    };
+    if args.poll_secs > MAX_SENSIBLE_POLL_SECS {
+        tracing::warn!(
+            "Polls are held open for {}s, HTTP clients and proxies between Studio and the server may drop requests open that long",
+            args.poll_secs
+        );
+    }
+    let (host, port, command_timeout) = (config.host, config.port, config.command_timeout);
+    if !host.is_loopback() {
+        tracing::warn!(
+            "Binding to {host}, the endpoints that control Studio are reachable from the network: /request and \
+             /response, /proxy, which queues any command including run_code, and /mock"
+        );
+    }
+
+    let server_state = Arc::new(Mutex::new(AppState::new(
+        config.poll_duration,
+        config.command_timeout,
+    )));
 
     let (close_tx, close_rx) = tokio::sync::oneshot::channel();
 
-    let listener =
-        tokio::net::TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), STUDIO_PLUGIN_PORT)).await;
+    let listener = tokio::net::TcpListener::bind((host, port)).await;
 
     let server_state_clone = Arc::clone(&server_state);
     let server_handle = if let Ok(listener) = listener {
@@ -52,8 +240,12 @@ async fn main() -> Result<()> {
             .route("/request", get(request_handler))
             .route("/response", post(response_handler))
             .route("/proxy", post(proxy_handler))
+            .route("/progress", post(progress_handler))
+            .route("/cancel/{id}", get(cancel_handler))
+            .route("/mock/{*path}", any(mock_handler))
+            .layer(DefaultBodyLimit::max(STUDIO_BODY_LIMIT))
             .with_state(server_state_clone);
-        tracing::info!("This MCP instance is HTTP server listening on {STUDIO_PLUGIN_PORT}");
+        tracing::info!("This MCP instance is HTTP server listening on {host}:{port}");
         tokio::spawn(async {
             axum::serve(listener, app)
                 .with_graceful_shutdown(async move {
@@ -65,18 +257,23 @@ async fn main() -> Result<()> {
     } else {
         tracing::info!("This MCP instance will use proxy since port is busy");
         tokio::spawn(async move {
-            dud_proxy_loop(server_state_clone, close_rx).await;
+            dud_proxy_loop(server_state_clone, close_rx, host, port, command_timeout).await;
         })
     };
 
-    // Create an instance of our counter router
-    let service = RBXStudioServer::new(Arc::clone(&server_state))
-        .serve(rmcp::transport::stdio())
-        .await
-        .inspect_err(|e| {
-            tracing::error!("serving error: {:?}", e);
-        })?;
-    service.waiting().await?;
+    match args.transport {
+        Transport::Stdio => {
+            // Create an instance of our counter router
+            let service = RBXStudioServer::new(Arc::clone(&server_state), config)
+                .serve(rmcp::transport::stdio())
+                .await
+                .inspect_err(|e| {
+                    tracing::error!("serving error: {:?}", e);
+                })?;
+            service.waiting().await?;
+        }
+        Transport::Http => serve_http(server_state, config, args.mcp_port).await?,
+    }
 
     close_tx.send(()).ok();
     tracing::info!("Waiting for web server to gracefully shutdown");
@@ -84,3 +281,30 @@ async fn main() -> Result<()> {
     tracing::info!("Bye!");
     Ok(())
 }
+
+/// Serves MCP over streamable HTTP at `/mcp` until interrupted. Each client session gets its own server sharing the
+/// plugin connection.
+async fn serve_http(state: PackedState, config: AppConfig, port: u16) -> Result<()> {
+    let host = config.host;
+    let listener = tokio::net::TcpListener::bind((host, port))
+        .await
+        .wrap_err_with(|| format!("Unable to listen for MCP clients on {host}:{port}"))?;
+    let http_config = StreamableHttpServerConfig::default();
+    let shutdown = http_config.cancellation_token.clone();
+    let sessions = Arc::new(LocalSessionManager::default());
+    state.lock().await.set_http_sessions(Arc::clone(&sessions));
+    let service = StreamableHttpService::new(
+        move || Ok(RBXStudioServer::new(Arc::clone(&state), config.clone())),
+        sessions,
+        http_config,
+    );
+    let app = axum::Router::new().nest_service("/mcp", service);
+    tracing::info!("Serving MCP clients at http://{host}:{port}/mcp");
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            tokio::signal::ctrl_c().await.ok();
+            shutdown.cancel();
+        })
+        .await?;
+    Ok(())
+}
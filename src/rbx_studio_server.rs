@@ -1,30 +1,90 @@
+use crate::config::AppConfig;
 use crate::error::{Report, Result};
-use axum::http::StatusCode;
+use crate::luau_export::{self, ScriptBuilder};
+use crate::screenshot;
+use crate::script_diff;
+use crate::script_files;
+use crate::selector;
+use axum::extract::Path;
+use axum::http::{header, StatusCode};
 use axum::response::IntoResponse;
 use axum::{extract::State, Json};
-use color_eyre::eyre::{eyre, Error, OptionExt};
+use color_eyre::eyre::{eyre, Error, OptionExt, WrapErr};
 use rmcp::{
+    handler::server::tool::ToolCallContext,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::{
-        CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo,
+        CallToolRequestParams, CallToolResult, Content, Implementation, ListToolsResult, Meta,
+        PaginatedRequestParams, ProgressNotificationParam, ProgressToken, ProtocolVersion,
+        ServerCapabilities, ServerInfo, Tool,
     },
-    schemars, tool, tool_handler, tool_router, ErrorData, ServerHandler,
+    schemars,
+    service::RequestContext,
+    tool, tool_router,
+    transport::streamable_http_server::session::local::LocalSessionManager,
+    ErrorData, Peer, RoleServer, ServerHandler,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::oneshot::Receiver;
 use tokio::sync::{mpsc, watch, Mutex};
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
 use uuid::Uuid;
 
+/// Default plugin port, matching what the plugin is built with unless RBX_MCP_PORT was set
 pub const STUDIO_PLUGIN_PORT: u16 = 44755;
-const LONG_POLL_DURATION: Duration = Duration::from_secs(15);
+const QUEUE_REDACT_LENGTH: usize = 200;
+const RECONNECT_ACK_TIMEOUT: Duration = Duration::from_secs(10);
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(60);
+/// Time allowed on top of a play mode script's own timeout to start and stop the playtest
+const PLAY_MODE_GRACE: Duration = Duration::from_secs(15);
+/// How long the id of a running command the client cancelled is kept for the plugin's late answer
+const CANCELLED_RETENTION: Duration = Duration::from_secs(600);
+/// Extra time a forwarded command gets, so the owning instance's own timeout error arrives first
+const PROXY_GRACE: Duration = Duration::from_secs(5);
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const WATCH_DEFAULT_SECS: u64 = 60;
+const WATCH_MAX_SECS: u64 = 600;
+/// How often a command still waiting on Studio reports progress, when the client asked for progress
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+/// Directory inside the files directory macros are saved to
+const MACROS_DIR: &str = "macros";
+/// How much of each step's response a macro replay reports
+const MACRO_RESPONSE_PREVIEW: usize = 1000;
+/// Tools that run arbitrary Luau like run_code, so disabling run_code disables them too
+const RUNS_CODE: &[&str] = &["run_code_json", "run_batch", "benchmark_code"];
+/// Plugin responses can carry whole script trees or images, well above axum's 2MB default
+pub const STUDIO_BODY_LIMIT: usize = 64 * 1024 * 1024;
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ToolArguments {
     args: ToolArgumentValues,
     id: Option<Uuid>,
+    #[serde(skip)]
+    queued_at: Option<Instant>,
+    /// Session of the server that queued the command, `None` for commands proxied from another server instance
+    #[serde(skip)]
+    session: Option<Uuid>,
+}
+
+tokio::task_local! {
+    /// Request context of the tool call being handled, so commands can report progress without every tool taking it
+    static TOOL_CALL: RequestContext<RoleServer>;
+}
+
+/// Progress a running command reported through the plugin's `reportProgress`
+#[derive(Deserialize, Debug)]
+pub struct PluginProgress {
+    id: Uuid,
+    progress: f64,
+    total: Option<f64>,
+    message: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -37,26 +97,222 @@ pub struct RunCommandResponse {
 pub struct AppState {
     process_queue: VecDeque<ToolArguments>,
     output_map: HashMap<Uuid, mpsc::UnboundedSender<Result<String>>>,
+    /// Progress posted by the plugin, by the id of the command it belongs to, only for calls with a progress token
+    progress_map: HashMap<Uuid, mpsc::UnboundedSender<PluginProgress>>,
+    /// Commands the client cancelled after the plugin picked them up and when, until the plugin answers. Plugins that
+    /// never answer leave them behind, so they are pruned after `CANCELLED_RETENTION` or once the plugin disconnects.
+    cancelled: HashMap<Uuid, Instant>,
+    /// How long a poll from the plugin is held open waiting for a command
+    poll_duration: Duration,
+    /// How long commands proxied from another server instance wait for the plugin, `None` to wait indefinitely
+    command_timeout: Option<Duration>,
+    /// Polls being held open, shared with the handlers so a dropped poll still counts down
+    open_polls: Arc<AtomicUsize>,
     waiter: watch::Receiver<()>,
     trigger: watch::Sender<()>,
+    stats: CommandStats,
+    /// Camera views saved by name, kept for the lifetime of the server process
+    camera_bookmarks: BTreeMap<String, Value>,
+    /// When the plugin last polled for a command, `None` until it first connects
+    last_poll: Option<Instant>,
+    /// Commands the plugin picked up and hasn't answered yet with the session that queued them, it doesn't poll while
+    /// running one. Commands whose caller gave up are dropped from here, so a plugin that died mid-command doesn't
+    /// count as connected forever.
+    in_flight: HashMap<Uuid, Option<Uuid>>,
+    /// Set when another server instance owns the plugin port and commands are forwarded to it
+    proxied: bool,
+    /// Canned responses served under `/mock/`, by path without the leading slash
+    http_mocks: BTreeMap<String, HttpMock>,
+    /// Name of the macro each session is recording and the tool calls recorded so far, by session
+    macro_recordings: HashMap<Uuid, (String, Vec<ToolArgumentValues>)>,
+    /// Macros recorded in this session, also saved to the files directory when one is configured
+    macros: BTreeMap<String, Vec<ToolArgumentValues>>,
+    /// Sessions of the clients connected over HTTP, `None` when serving a single client over stdio
+    http_sessions: Option<Arc<LocalSessionManager>>,
 }
 pub type PackedState = Arc<Mutex<AppState>>;
 
 impl AppState {
-    pub fn new() -> Self {
+    pub fn new(poll_duration: Duration, command_timeout: Option<Duration>) -> Self {
         let (trigger, waiter) = watch::channel(());
         Self {
             process_queue: VecDeque::new(),
             output_map: HashMap::new(),
+            progress_map: HashMap::new(),
+            cancelled: HashMap::new(),
+            poll_duration,
+            command_timeout,
+            open_polls: Arc::new(AtomicUsize::new(0)),
             waiter,
             trigger,
+            stats: CommandStats::new(),
+            camera_bookmarks: BTreeMap::new(),
+            last_poll: None,
+            in_flight: HashMap::new(),
+            proxied: false,
+            http_mocks: BTreeMap::new(),
+            macro_recordings: HashMap::new(),
+            macros: BTreeMap::new(),
+            http_sessions: None,
+        }
+    }
+
+    /// Lets tools count the sessions of clients connected over HTTP
+    pub fn set_http_sessions(&mut self, sessions: Arc<LocalSessionManager>) {
+        self.http_sessions = Some(sessions);
+    }
+
+    /// Whether the plugin polled within `window`, counting from server start before its first poll. A poll that is
+    /// still open counts as connected, however long polls are held.
+    fn studio_connected(&self, window: Duration) -> bool {
+        let last_seen = self.last_poll.unwrap_or(self.stats.started_at);
+        self.proxied
+            || !self.in_flight.is_empty()
+            || self.open_polls.load(Ordering::Relaxed) > 0
+            || last_seen.elapsed() <= window
+    }
+
+    /// Forgets a command whose caller gave up. A queued command is dropped before the plugin picks it up, a running
+    /// one is reported through `/cancel` so code that checks for cancellation can stop, and its late response is
+    /// dropped.
+    fn cancel_command(&mut self, id: Uuid) {
+        self.output_map.remove(&id);
+        self.progress_map.remove(&id);
+        self.process_queue.retain(|command| command.id != Some(id));
+        let now = Instant::now();
+        self.cancelled
+            .retain(|_, cancelled_at| now.duration_since(*cancelled_at) < CANCELLED_RETENTION);
+        if self.in_flight.remove(&id).is_some() {
+            self.cancelled.insert(id, now);
+        }
+    }
+}
+
+/// How long to wait for the plugin to answer, commands that run for a set time get at least that long
+fn command_timeout(timeout: Option<Duration>, args: &ToolArgumentValues) -> Option<Duration> {
+    let expected = match args {
+        ToolArgumentValues::RunScriptInPlayMode(args) => {
+            Duration::from_secs(args.timeout.unwrap_or(100).into()) + PLAY_MODE_GRACE
+        }
+        _ => Duration::ZERO,
+    };
+    Some(timeout?.max(expected))
+}
+
+/// Cancels a command when the future waiting for its response is dropped before it arrives, e.g. when it loses a
+/// `tokio::select!` or the other server instance gives up on a proxied command and disconnects
+struct PendingCommand {
+    state: PackedState,
+    id: Uuid,
+    done: bool,
+}
+
+impl PendingCommand {
+    fn new(state: &PackedState, id: Uuid) -> Self {
+        Self {
+            state: Arc::clone(state),
+            id,
+            done: false,
+        }
+    }
+
+    fn finish(mut self) {
+        self.done = true;
+    }
+
+    async fn cancel(mut self) {
+        self.done = true;
+        self.state.lock().await.cancel_command(self.id);
+    }
+}
+
+impl Drop for PendingCommand {
+    fn drop(&mut self) {
+        if !self.done {
+            let (state, id) = (Arc::clone(&self.state), self.id);
+            tokio::spawn(async move { state.lock().await.cancel_command(id) });
+        }
+    }
+}
+
+/// Name of the tool call, e.g. `SetProperty`, as it's tagged when sent to the plugin
+fn step_name(step: &ToolArgumentValues) -> String {
+    match serde_json::to_value(step) {
+        Ok(Value::Object(tagged)) => tagged.keys().next().cloned().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Name of the tool a step calls, e.g. `set_property`. Steps the server sends on behalf of another tool, like
+/// `ApplyScripts`, have no tool of that name.
+fn step_tool_name(step: &ToolArgumentValues) -> String {
+    let mut name = String::new();
+    for c in step_name(step).chars() {
+        if c.is_ascii_uppercase() && !name.is_empty() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+/// Canned response registered by `register_http_mock`
+struct HttpMock {
+    status: StatusCode,
+    content_type: String,
+    body: String,
+    hits: u64,
+}
+
+/// Counts of commands answered by the plugin since the server started
+struct CommandStats {
+    started_at: Instant,
+    completed: u64,
+    failed: u64,
+    /// Completion times within the last [`THROUGHPUT_WINDOW`]
+    recent: VecDeque<Instant>,
+}
+
+impl CommandStats {
+    fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            completed: 0,
+            failed: 0,
+            recent: VecDeque::new(),
+        }
+    }
+
+    fn record(&mut self, success: bool) {
+        self.completed += 1;
+        if !success {
+            self.failed += 1;
+        }
+        let now = Instant::now();
+        self.recent.push_back(now);
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > THROUGHPUT_WINDOW)
+        {
+            self.recent.pop_front();
         }
     }
 }
 
 impl ToolArguments {
-    fn new(args: ToolArgumentValues) -> (Self, Uuid) {
-        Self { args, id: None }.with_id()
+    fn new(args: ToolArgumentValues, session: Uuid) -> (Self, Uuid) {
+        Self {
+            args,
+            id: None,
+            queued_at: None,
+            session: Some(session),
+        }
+        .with_id()
     }
     fn with_id(self) -> (Self, Uuid) {
         let id = Uuid::new_v4();
@@ -64,209 +320,3686 @@ impl ToolArguments {
             Self {
                 args: self.args,
                 id: Some(id),
+                queued_at: Some(Instant::now()),
+                session: self.session,
             },
             id,
         )
     }
-}
-#[derive(Clone)]
-pub struct RBXStudioServer {
-    state: PackedState,
-    tool_router: ToolRouter<Self>,
-}
+    /// Summary of a queued command with long string arguments (e.g. code) truncated
+    fn describe(&self) -> Value {
+        let (tool, args) = match serde_json::to_value(&self.args) {
+            Ok(Value::Object(map)) => map.into_iter().next().unwrap_or_default(),
+            _ => (String::new(), Value::Null),
+        };
+        json!({
+            "id": self.id,
+            "tool": tool,
+            "queued_secs": self.queued_at.map(|t| t.elapsed().as_secs_f64()),
+            "args": redact(args),
+        })
+    }
+}
+
+fn redact(value: Value) -> Value {
+    match value {
+        Value::String(s) if s.chars().count() > QUEUE_REDACT_LENGTH => {
+            let kept: String = s.chars().take(QUEUE_REDACT_LENGTH).collect();
+            Value::String(format!(
+                "{kept}... ({} chars redacted)",
+                s.chars().count() - QUEUE_REDACT_LENGTH
+            ))
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(redact).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, redact(v))).collect()),
+        other => other,
+    }
+}
+#[derive(Clone)]
+pub struct RBXStudioServer {
+    state: PackedState,
+    config: Arc<AppConfig>,
+    tool_router: ToolRouter<Self>,
+    /// Tells this MCP session's commands apart from those of other sessions sharing the state
+    session: Uuid,
+}
+
+impl ServerHandler for RBXStudioServer {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool_call = ToolCallContext::new(self, request, context.clone());
+        TOOL_CALL
+            .scope(context, self.tool_router.call(tool_call))
+            .await
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult {
+            tools: self.tool_router.list_all(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::LATEST,
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: "Roblox_Studio".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                title: Some("Roblox Studio MCP Server".to_string()),
+                icons: None,
+                website_url: None,
+            },
+            instructions: Some(
+                "You must aware of current studio mode before using any tools, infer the mode from conversation context or get_studio_mode.
+User run_code to query data from Roblox Studio place or to change it
+After calling run_script_in_play_mode, the datamodel status will be reset to stop mode.
+Prefer using start_stop_play tool instead run_script_in_play_mode, Only used run_script_in_play_mode to run one time unit test code on server datamodel.
+Tools taking instance paths expect dot-separated names from the DataModel, e.g. game.Workspace.Model.Part.
+"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RunCode {
+    #[schemars(description = "Code to run")]
+    command: String,
+    #[schemars(
+        description = "Run with a fresh global environment so globals don't leak between calls. Defaults to false. Only globals are isolated: _G, shared, required modules and the place itself are still shared"
+    )]
+    sandboxed: Option<bool>,
+    #[schemars(
+        description = "JSON payload available to the code as the local `args`, instead of interpolating values into the code. Datatypes may use the {type, value} encoding, e.g. {\"type\": \"Vector3\", \"value\": [0, 5, 0]}. When given, the return value is also reported as JSON after [RETURNED JSON]"
+    )]
+    args: Option<Value>,
+}
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct InsertModel {
+    #[schemars(description = "Query to search for the model")]
+    query: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetConsoleOutput {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetStudioMode {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetQueuedCommands {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct StartStopPlay {
+    #[schemars(
+        description = "Mode to start or stop, must be start_play, stop, or run_server. Don't use run_server unless you are sure no client/player is needed."
+    )]
+    mode: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RunScriptInPlayMode {
+    #[schemars(description = "Code to run")]
+    code: String,
+    #[schemars(description = "Timeout in seconds, defaults to 100 seconds")]
+    timeout: Option<u32>,
+    #[schemars(description = "Mode to run in, must be start_play or run_server")]
+    mode: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetHumanoidConfig {
+    #[schemars(
+        description = "Path to a Humanoid or a character Model containing one, e.g. game.Workspace.Rig. Omit to use the StarterPlayer character defaults"
+    )]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetHumanoidConfig {
+    #[schemars(
+        description = "Path to a Humanoid or a character Model containing one, e.g. game.Workspace.Rig. Omit to set the StarterPlayer character defaults"
+    )]
+    path: Option<String>,
+    #[schemars(description = "WalkSpeed between 0 and 1000")]
+    walk_speed: Option<f64>,
+    #[schemars(description = "JumpPower between 0 and 1000, also enables UseJumpPower")]
+    jump_power: Option<f64>,
+    #[schemars(description = "HipHeight between 0 and 100, only valid on a Humanoid")]
+    hip_height: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetConfig {
+    #[schemars(description = "Path to the config ModuleScript or instance holding attributes")]
+    path: String,
+    #[schemars(
+        description = "Config convention, must be module (a ModuleScript returning a table) or attributes (the instance's attributes)"
+    )]
+    mode: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetConfig {
+    #[schemars(description = "Path to the config ModuleScript or instance holding attributes")]
+    path: String,
+    #[schemars(
+        description = "Config convention, must be module (a ModuleScript returning a table) or attributes (the instance's attributes)"
+    )]
+    mode: String,
+    #[schemars(
+        description = "JSON object of keys to set, merged into the existing config. Roblox datatypes use the { type, value } encoding returned by get_config"
+    )]
+    values: serde_json::Map<String, serde_json::Value>,
+    #[schemars(description = "Keys to remove from the config")]
+    remove: Option<Vec<String>>,
+    #[schemars(
+        description = "Create the config if it doesn't exist, as a ModuleScript (mode module) or Configuration (mode attributes) named after the last path segment"
+    )]
+    create: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CaptureSelectionThumbnail {
+    #[schemars(
+        description = "Longest side of the thumbnail in pixels, between 64 and 1024, defaults to 512. Smaller images keep the payload small"
+    )]
+    resolution: Option<u32>,
+    #[schemars(
+        description = "Average the pixels each thumbnail pixel covers when downscaling instead of picking the nearest one. Smoother edges at a small extra cost, defaults to false"
+    )]
+    anti_aliasing: Option<bool>,
+    #[schemars(
+        description = "Studio render quality level to capture at, from 1 (lowest) to 21 (highest). Defaults to the current quality, which is restored afterwards"
+    )]
+    render_quality: Option<u8>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindBrokenAssets {
+    #[schemars(description = "Path of the subtree to scan, defaults to game.Workspace")]
+    path: Option<String>,
+    #[schemars(
+        description = "Optional repair mode, clear (empties the broken reference) or replace (sets replacement_id). Omit for a read-only report"
+    )]
+    repair: Option<String>,
+    #[schemars(description = "Asset id to use when repair is replace, e.g. rbxassetid://123")]
+    replacement_id: Option<String>,
+    #[schemars(description = "Must be true for repairs to be applied")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SnapIncrements {
+    #[schemars(description = "New move snap increment in studs, omit to only read")]
+    move_increment: Option<f64>,
+    #[schemars(description = "New rotate snap increment in degrees, omit to only read")]
+    rotate_increment: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetRecentChanges {
+    #[schemars(description = "Maximum number of instances to return, defaults to 50, at most 200")]
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ImportConfigFile {
+    #[schemars(description = "JSON file to import, relative to the server's files directory")]
+    file: String,
+    #[schemars(description = "Path of the config to write, created if it doesn't exist")]
+    path: String,
+    #[schemars(
+        description = "Config convention, must be module (a ModuleScript returning a table) or attributes (the instance's attributes)"
+    )]
+    mode: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetScriptSources {
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct DiffScripts {
+    #[schemars(description = "Path of the original script")]
+    path: String,
+    #[schemars(description = "Path of the script to compare against, omit when passing source")]
+    other_path: Option<String>,
+    #[schemars(
+        description = "Proposed new source to compare against, omit when passing other_path"
+    )]
+    source: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetScriptSource {
+    path: String,
+    source: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ApplyScriptPatch {
+    #[schemars(description = "Path of the script to patch")]
+    path: String,
+    #[schemars(description = "Unified diff to apply, e.g. as returned by diff_scripts")]
+    patch: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetCapabilities {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetServerConfig {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetServerResources {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetServerStats {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetEnumValues {
+    #[schemars(description = "Enum to list, e.g. Material. Omit to look it up from a property")]
+    enum_name: Option<String>,
+    #[schemars(description = "Enum-typed property to list values for, e.g. Material")]
+    property: Option<String>,
+    #[schemars(description = "Class that has the property, e.g. Part")]
+    class_name: Option<String>,
+    #[schemars(description = "Path of an instance that has the property, instead of class_name")]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetOutputLog {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ExportOutputLog {
+    #[schemars(
+        description = "File to write, relative to the server's files directory. Defaults to studio-output-<unix time>.log"
+    )]
+    file: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ResolveHandles {
+    #[schemars(description = "Handles returned by tools that created instances")]
+    handles: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetCameraSettings {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetCameraSettings {
+    #[schemars(description = "Studio \"Camera Speed\" between 0.01 and 10")]
+    camera_speed: Option<f64>,
+    #[schemars(description = "Studio \"Camera Shift Speed\" between 0.01 and 10")]
+    shift_speed: Option<f64>,
+    #[schemars(description = "Studio \"Camera Mouse Wheel Speed\" between 0.01 and 10")]
+    mouse_wheel_speed: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetPlaytestErrors {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CreateFolder {
+    #[schemars(description = "Path of the parent to create the folder under, e.g. game.Workspace")]
+    parent_path: String,
+    #[schemars(
+        description = "Name of the folder. An existing Folder with this name under the parent is reused"
+    )]
+    name: String,
+    #[schemars(description = "Paths of instances to move into the folder")]
+    paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct PreviewTween {
+    #[schemars(description = "Path of the instance to animate, e.g. game.Workspace.Door")]
+    path: String,
+    #[schemars(description = "Name of the property to tween, e.g. CFrame or Transparency")]
+    property: String,
+    #[schemars(
+        description = "Value to tween to. Datatypes use the {type, value} encoding or a plain value matching the property type, e.g. [0, 10, 0] for a Vector3"
+    )]
+    end_value: Value,
+    #[schemars(
+        description = "Value to set before the tween starts. Defaults to the current value"
+    )]
+    start_value: Option<Value>,
+    #[schemars(description = "Duration in seconds, up to 30. Defaults to 1")]
+    duration: Option<f64>,
+    #[schemars(
+        description = "Enum.EasingStyle item name, e.g. Linear or Bounce. Defaults to Quad"
+    )]
+    easing_style: Option<String>,
+    #[schemars(description = "Enum.EasingDirection item name: In, Out or InOut. Defaults to Out")]
+    easing_direction: Option<String>,
+    #[schemars(
+        description = "Restore the original value after the preview. Defaults to true; set false to keep the end value"
+    )]
+    restore: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindDisabledScripts {
+    #[schemars(description = "Path of the subtree to scan, defaults to the whole place")]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct EnableDisabledScripts {
+    #[schemars(
+        description = "Path of the subtree whose disabled scripts are enabled, defaults to the whole place. Ignored when paths is given"
+    )]
+    path: Option<String>,
+    #[schemars(description = "Paths of specific scripts to enable")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Must be true for scripts to be enabled")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CollectScripts {
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ExportScripts {
+    #[schemars(description = "Path of the subtree to export, e.g. game.ServerScriptService")]
+    path: String,
+    #[schemars(
+        description = "Directory relative to the server's files directory to write the scripts to, defaults to scripts"
+    )]
+    directory: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ApplyScripts {
+    path: String,
+    #[schemars(skip)]
+    scripts: Vec<script_files::ScriptEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ImportScripts {
+    #[schemars(
+        description = "Path of the instance to import into, e.g. game.ServerScriptService. Folders are mapped to child instances"
+    )]
+    path: String,
+    #[schemars(
+        description = "Directory relative to the server's files directory to read .lua and .luau files from, defaults to scripts"
+    )]
+    directory: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetFonts {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetFont {
+    #[schemars(description = "Paths of the TextLabels, TextButtons or TextBoxes to update")]
+    paths: Vec<String>,
+    #[schemars(
+        description = "Enum.Font name such as Gotham or SourceSans, or a font family asset such as rbxasset://fonts/families/Roboto.json"
+    )]
+    font: String,
+    #[schemars(
+        description = "Enum.FontWeight name such as Bold. Defaults to the font's own weight"
+    )]
+    weight: Option<String>,
+    #[schemars(
+        description = "Enum.FontStyle name, Normal or Italic. Defaults to the font's own style"
+    )]
+    style: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindStructuralIssues {
+    #[schemars(description = "Path of the subtree to scan, defaults to the whole place")]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetSky {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetSky {
+    #[schemars(description = "Back skybox face asset id, e.g. rbxassetid://123")]
+    skybox_bk: Option<String>,
+    #[schemars(description = "Bottom skybox face asset id")]
+    skybox_dn: Option<String>,
+    #[schemars(description = "Front skybox face asset id")]
+    skybox_ft: Option<String>,
+    #[schemars(description = "Left skybox face asset id")]
+    skybox_lf: Option<String>,
+    #[schemars(description = "Right skybox face asset id")]
+    skybox_rt: Option<String>,
+    #[schemars(description = "Top skybox face asset id")]
+    skybox_up: Option<String>,
+    #[schemars(description = "Sun texture asset id")]
+    sun_texture: Option<String>,
+    #[schemars(description = "Moon texture asset id")]
+    moon_texture: Option<String>,
+    #[schemars(description = "Sun size in degrees between 0 and 60")]
+    sun_angular_size: Option<f64>,
+    #[schemars(description = "Moon size in degrees between 0 and 60")]
+    moon_angular_size: Option<f64>,
+    #[schemars(description = "Number of stars between 0 and 5000")]
+    star_count: Option<u32>,
+    #[schemars(description = "Whether the sun and moon are shown")]
+    celestial_bodies_shown: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SimulatePhysics {
+    #[schemars(
+        description = "Paths of up to 200 BaseParts to track, e.g. game.Workspace.Tower.Block1"
+    )]
+    paths: Vec<String>,
+    #[schemars(description = "Seconds to simulate, up to 10. Defaults to 2")]
+    duration: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindDuplicateAssets {
+    #[schemars(description = "Path of the subtree to scan, defaults to game.Workspace")]
+    path: Option<String>,
+    #[schemars(
+        description = "Merge MaterialVariants in MaterialService with identical settings into one and repoint parts using them. Omit for a read-only report"
+    )]
+    consolidate: Option<bool>,
+    #[schemars(description = "Must be true for consolidate to be applied")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GroupInstances {
+    #[schemars(
+        description = "Paths of the instances to group, defaults to the current Studio selection"
+    )]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Name of the new Model, defaults to Model")]
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct UngroupModel {
+    #[schemars(description = "Path of the Model to ungroup")]
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ReconnectPlugin {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetTeleportConfig {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ListContextActions {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FireContextAction {
+    #[schemars(description = "Name of the bound action")]
+    action: String,
+    #[schemars(
+        description = "UserInputStates to call the action with in order: Begin, Change, End or Cancel. Defaults to Begin then End, like a key press"
+    )]
+    states: Option<Vec<String>>,
+    #[schemars(description = "Must be true for the action to be fired")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindBrokenWelds {
+    #[schemars(description = "Path of the subtree to scan, defaults to game.Workspace")]
+    path: Option<String>,
+    #[schemars(
+        description = "Weld each detached part to the rest of its Model with a WeldConstraint. Omit for a read-only report"
+    )]
+    repair: Option<bool>,
+    #[schemars(description = "Must be true for repairs to be applied")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetPivot {
+    #[schemars(description = "Path of the BasePart or Model")]
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetPivot {
+    #[schemars(description = "Path of the BasePart or Model")]
+    path: String,
+    #[schemars(
+        description = "New PivotOffset relative to the part (or the Model's PrimaryPart), as a CFrame: [x, y, z] or the 12 CFrame components"
+    )]
+    pivot_offset: Option<Value>,
+    #[schemars(description = "New pivot in world space as a CFrame, the geometry doesn't move")]
+    world_pivot: Option<Value>,
+    #[schemars(description = "Move the object so its pivot ends up at this CFrame, like PivotTo")]
+    move_to: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetGameSettings {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetGameSettings {
+    #[schemars(
+        description = "Settings to change by name, e.g. {\"gravity\": 196.2, \"camera_mode\": \"LockFirstPerson\"}. get_game_settings lists the writable names; enums take item names"
+    )]
+    values: serde_json::Map<String, serde_json::Value>,
+    #[schemars(description = "Must be true for the settings to be changed")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct LayoutUi {
+    #[schemars(description = "Path of a ScreenGui or GuiObject, e.g. game.StarterGui.HUD")]
+    path: String,
+    #[schemars(description = "Target screen size in pixels as [width, height], e.g. [1280, 720]")]
+    resolution: [f64; 2],
+    #[schemars(
+        description = "Shift the Position offset of elements that overflow the screen so they fit. Omit for a read-only report"
+    )]
+    fit: Option<bool>,
+    #[schemars(description = "Must be true for fit to be applied")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetPluginPermissions {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct WatchPlaytestErrors {
+    #[schemars(
+        description = "Seconds to watch before returning, defaults to 60 and is capped at 600"
+    )]
+    timeout_secs: Option<u64>,
+    /// Sequence number of the last error already reported, set by the server while polling
+    #[schemars(skip)]
+    #[serde(default)]
+    cursor: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct OrganizeByClass {
+    #[schemars(description = "Path of the container to clean up, defaults to game.Workspace")]
+    path: Option<String>,
+    #[schemars(
+        description = "Map of exact class names to destination paths, e.g. {\"Script\": \"game.ServerScriptService\"}. Replaces the defaults, which send Scripts to ServerScriptService, LocalScripts to StarterPlayerScripts and ModuleScripts and remotes to ReplicatedStorage"
+    )]
+    rules: Option<HashMap<String, String>>,
+    #[schemars(
+        description = "Also move matching instances nested in folders and models, not just direct children. Defaults to false"
+    )]
+    recursive: Option<bool>,
+    #[schemars(description = "Must be true for instances to be moved")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetValueObjects {
+    #[schemars(
+        description = "Path of a ValueBase instance, or of a container whose ValueBase descendants are all returned"
+    )]
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetValueObject {
+    #[schemars(
+        description = "Path of the ValueBase instance, e.g. game.ReplicatedStorage.Config.MaxPlayers"
+    )]
+    path: String,
+    #[schemars(
+        description = "New Value matching the class: a number, string or boolean, a datatype like [0, 5, 0] for a Vector3Value or the 12 CFrame components, or for an ObjectValue the path of the referenced instance (null clears it)"
+    )]
+    value: Value,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetRequireGraph {
+    #[schemars(
+        description = "Path of the subtree whose scripts are scanned, defaults to the whole place"
+    )]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetStreamingSettings {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetStreamingSettings {
+    #[schemars(description = "Workspace.StreamingEnabled")]
+    streaming_enabled: Option<bool>,
+    #[schemars(description = "Workspace.StreamingTargetRadius in studs")]
+    target_radius: Option<f64>,
+    #[schemars(description = "Workspace.StreamingMinRadius in studs, at most target_radius")]
+    min_radius: Option<f64>,
+    #[schemars(description = "Workspace.StreamingPauseMode item name, e.g. ClientPhysicsPause")]
+    pause_mode: Option<String>,
+    #[schemars(
+        description = "Workspace.StreamingIntegrityMode item name, e.g. MinimumRadiusPause"
+    )]
+    integrity_mode: Option<String>,
+    #[schemars(description = "Workspace.StreamOutBehavior item name, e.g. Opportunistic")]
+    stream_out_behavior: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetBoundingBoxes {
+    #[schemars(description = "Path of the subtree, e.g. game.Workspace.Map")]
+    path: String,
+    #[schemars(
+        description = "Maximum number of parts to list, defaults to 500 and is capped at 5000"
+    )]
+    max_parts: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetFidelity {
+    #[schemars(
+        description = "Paths of the instances to inspect along with their descendants, defaults to the current Studio selection"
+    )]
+    paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetFidelity {
+    #[schemars(
+        description = "Paths of the instances to change along with their descendants, defaults to the current Studio selection"
+    )]
+    paths: Option<Vec<String>>,
+    #[schemars(
+        description = "RenderFidelity item name for MeshParts and unions: Automatic, Precise or Performance"
+    )]
+    render_fidelity: Option<String>,
+    #[schemars(
+        description = "CollisionFidelity item name for MeshParts and unions, e.g. Box, Hull, Default or PreciseConvexDecomposition"
+    )]
+    collision_fidelity: Option<String>,
+    #[schemars(
+        description = "LevelOfDetail item name for Models: Automatic, StreamingMesh or Disabled"
+    )]
+    level_of_detail: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindNameReferences {
+    #[schemars(
+        description = "Path of the subtree whose instance names are looked for, e.g. game.Workspace.Map"
+    )]
+    path: String,
+    #[schemars(description = "Only look for this instance name, e.g. one you plan to rename")]
+    name: Option<String>,
+    #[schemars(
+        description = "Path of the subtree whose scripts are scanned, defaults to the whole place"
+    )]
+    scripts_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindScaleOutliers {
+    #[schemars(description = "Path of the model or subtree to analyze")]
+    path: String,
+    #[schemars(
+        description = "How many times larger or smaller than the median part a part must be to be flagged, defaults to 20"
+    )]
+    threshold: Option<f64>,
+    #[schemars(
+        description = "Resize each outlier around its position to the median part scale. Omit for a read-only report"
+    )]
+    normalize: Option<bool>,
+    #[schemars(description = "Must be true for normalize to be applied")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetEffect {
+    #[schemars(description = "Path of an existing ParticleEmitter, Beam or Trail to configure")]
+    path: Option<String>,
+    #[schemars(description = "Path of the part (or attachment) to create a new effect under")]
+    parent_path: Option<String>,
+    #[schemars(description = "Class of the new effect: ParticleEmitter, Beam or Trail")]
+    class_name: Option<String>,
+    #[schemars(description = "Name of the new effect, defaults to its class name")]
+    name: Option<String>,
+    #[schemars(
+        description = "Properties to set by name. Datatypes use the {type, value} encoding or plain values matching the property, e.g. [[0, 1, 0, 0], [1, 0, 0, 1]] for a ColorSequence or [[0, 1], [1, 0]] for a NumberSequence; enums take item names and Attachment0/Attachment1 take paths"
+    )]
+    properties: Option<serde_json::Map<String, Value>>,
+    #[schemars(
+        description = "Emit this many particles right away to preview a ParticleEmitter in edit mode, at most 500"
+    )]
+    emit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetWorkspaceThresholds {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetWorkspaceThresholds {
+    #[schemars(
+        description = "Workspace.FallenPartsDestroyHeight, the height below which parts are destroyed, between -50000 and 50000"
+    )]
+    fallen_parts_destroy_height: Option<f64>,
+    #[schemars(description = "Workspace.AirDensity used for aerodynamic forces, between 0 and 1")]
+    air_density: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SaveCameraBookmark {
+    #[schemars(
+        description = "Name of the bookmark, replaces an existing bookmark with the same name"
+    )]
+    name: String,
+    #[schemars(
+        description = "Camera CFrame to store as [x, y, z] or the 12 CFrame components, defaults to the current Studio camera"
+    )]
+    cframe: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GoToCameraBookmark {
+    #[schemars(description = "Name of the bookmark to move the camera to")]
+    name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ListCameraBookmarks {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RemoveCameraBookmark {
+    #[schemars(description = "Name of the bookmark to remove")]
+    name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetCameraView {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetCameraView {
+    cframe: Value,
+    focus: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindDeprecatedApis {
+    #[schemars(
+        description = "Path of the subtree whose scripts are scanned, defaults to the whole place"
+    )]
+    path: Option<String>,
+    #[schemars(
+        description = "Identifiers to look for instead of the built-in list. Plain names match global calls like wait(, names starting with : match method calls like :remove("
+    )]
+    denylist: Option<Vec<String>>,
+    #[schemars(description = "Identifiers to leave out of the list, e.g. [\"tick\"]")]
+    allow: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetCollisionGroup {
+    #[schemars(description = "Name of a collision group registered with PhysicsService")]
+    group: String,
+    #[schemars(
+        description = "Paths of parts, models or folders to change, defaults to the current Studio selection. Parts inside them are changed too"
+    )]
+    paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindMissingPrimaryParts {
+    #[schemars(description = "Path of the subtree to scan, defaults to game.Workspace")]
+    path: Option<String>,
+    #[schemars(
+        description = "Set each Model's PrimaryPart to the suggested candidate. Omit for a read-only report"
+    )]
+    repair: Option<bool>,
+    #[schemars(description = "Must be true for repairs to be applied")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetSelection {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RunCodeJson {
+    #[schemars(description = "Code to run, its return value is reported")]
+    command: String,
+    #[schemars(
+        description = "JSON payload available to the code as the local `args`, with the same encoding as run_code"
+    )]
+    args: Option<Value>,
+    #[schemars(description = "How many tables deep to serialize, defaults to 8, at most 16")]
+    max_depth: Option<u32>,
+    #[schemars(description = "How many table entries to serialize in total, defaults to 1000")]
+    max_items: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetSelection {
+    #[schemars(
+        description = "Paths of the instances to select, e.g. game.Workspace.Part. An empty list clears the selection"
+    )]
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetPlaceIcons {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CreateInstance {
+    #[schemars(description = "Class of the new instance, e.g. Part or PointLight")]
+    class_name: String,
+    #[schemars(description = "Path of the parent, e.g. game.Workspace")]
+    parent_path: String,
+    #[schemars(
+        description = "Properties to set, by name. Values use the {type, value} encoding, untagged values are read as the property's type, e.g. {\"Size\": [4, 1, 2], \"Material\": \"Neon\", \"Name\": \"Floor\"}"
+    )]
+    properties: Option<HashMap<String, Value>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindOverlappingParts {
+    #[schemars(
+        description = "Paths of the parts, models or folders to check along with their descendants, defaults to the current Studio selection"
+    )]
+    paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct DeleteInstance {
+    #[schemars(description = "Paths of the instances to delete, e.g. game.Workspace.Part")]
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetLoadingGui {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetLoadingGui {
+    #[schemars(description = "StarterGui.ResetPlayerGuiOnSpawn")]
+    reset_player_gui_on_spawn: Option<bool>,
+    #[schemars(description = "StarterGui.ShowDevelopmentGui")]
+    show_development_gui: Option<bool>,
+    #[schemars(description = "StarterGui.ScreenOrientation item name, e.g. LandscapeSensor")]
+    screen_orientation: Option<String>,
+    #[schemars(
+        description = "ScreenGui to use as the loading screen. It's moved into ReplicatedFirst next to a generated LocalScript that shows it in place of the default loading screen until the game has loaded"
+    )]
+    gui_path: Option<String>,
+    #[schemars(
+        description = "Also make the generated script pass the ScreenGui to TeleportService:SetTeleportGui, so it shows when players teleport out. Needs gui_path"
+    )]
+    teleport_gui: Option<bool>,
+    #[schemars(description = "Must be true when gui_path is given")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetProperties {
+    #[schemars(description = "Path of the instance, e.g. game.Workspace.Part")]
+    path: String,
+    #[schemars(
+        description = "Names of the properties to read. Omit or leave empty for Name, ClassName, Parent, Archivable and the common properties of the instance's class"
+    )]
+    properties: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindRunContextIssues {
+    #[schemars(description = "Path of the subtree to scan, defaults to the whole place")]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetProperty {
+    #[schemars(description = "Path of the instance, e.g. game.Workspace.Part")]
+    path: String,
+    #[schemars(description = "Name of the property, e.g. Size")]
+    property: String,
+    #[schemars(
+        description = "New value in the encoding get_properties returns. Untagged values are read as the property's type, e.g. [4, 1, 2] for a Vector3, \"Neon\" for Material or a path for an Instance property"
+    )]
+    value: Value,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RegisterHttpMock {
+    #[schemars(
+        description = "Path under /mock/ to answer, e.g. weather/today. Replaces an existing mock with the same path"
+    )]
+    path: String,
+    #[schemars(
+        description = "Response body. Strings are sent as is, other JSON values are serialized"
+    )]
+    body: Value,
+    #[schemars(description = "HTTP status code, defaults to 200")]
+    status: Option<u16>,
+    #[schemars(
+        description = "Content-Type header, defaults to text/plain for string bodies and application/json otherwise"
+    )]
+    content_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct UnregisterHttpMock {
+    #[schemars(description = "Path of the mock to remove, omit to remove all mocks")]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetStarterDiff {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Undo {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Redo {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct StartMacroRecording {
+    #[schemars(
+        description = "Name to save the macro as, letters, digits, - and _ only. Replaces a macro with the same name"
+    )]
+    name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct StopMacroRecording {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ReplayMacro {
+    #[schemars(description = "Name of the macro to replay")]
+    name: String,
+    #[schemars(description = "Stop at the first step that fails, defaults to true")]
+    stop_on_error: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ListMacros {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RunBatch {
+    #[schemars(description = "Commands to run in order, each like a run_code call")]
+    commands: Vec<RunCode>,
+    #[schemars(
+        description = "Skip the remaining commands once one errors, defaults to true. When false every command runs regardless"
+    )]
+    stop_on_error: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CheckUiDevices {
+    #[schemars(
+        description = "Path of a ScreenGui or GuiObject, e.g. game.StarterGui.HUD. Defaults to the first selected GUI object"
+    )]
+    path: Option<String>,
+    #[schemars(
+        description = "Devices to check, any of phone (844x390), phone_portrait (390x844), tablet (1180x820), laptop (1366x768), desktop (1920x1080) and console (3840x2160). Defaults to all of them"
+    )]
+    devices: Option<Vec<String>>,
+    #[schemars(
+        description = "Convert the Position and Size of elements that are off screen on any checked device to scale, keeping their current place in the Studio viewport. Omit for a read-only report"
+    )]
+    fix: Option<bool>,
+    #[schemars(description = "Must be true for fix to be applied")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ExportMacroScript {
+    #[schemars(
+        description = "Name of a macro recorded in this or an earlier session, or of the macro being recorded"
+    )]
+    name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetSoundSettings {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetSoundSettings {
+    #[schemars(
+        description = "SoundService.AmbientReverb ReverbType item name, e.g. Cave or NoReverb"
+    )]
+    ambient_reverb: Option<String>,
+    #[schemars(
+        description = "SoundService.DistanceFactor, studs per meter used for the doppler effect, greater than 0"
+    )]
+    distance_factor: Option<f64>,
+    #[schemars(
+        description = "SoundService.RolloffScale, how quickly 3D sounds fade with distance, at least 0"
+    )]
+    rolloff_scale: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindUnusedScripts {
+    #[schemars(
+        description = "Path of the subtree to check, defaults to the whole place. Requires are always followed across the whole place"
+    )]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetNetworkOwnership {
+    #[schemars(
+        description = "Path of a BasePart, or of a Model or folder to report each unanchored assembly in, e.g. game.Workspace.Car"
+    )]
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetNetworkOwnership {
+    #[schemars(
+        description = "Path of the BasePart, or of a Model with a PrimaryPart. The whole assembly changes owner"
+    )]
+    path: String,
+    #[schemars(
+        description = "Name of the player in the playtest to give ownership to, or \"server\". Defaults to the server"
+    )]
+    owner: Option<String>,
+    #[schemars(
+        description = "Hand ownership back to the engine's automatic assignment instead of a fixed owner"
+    )]
+    auto: Option<bool>,
+    #[schemars(description = "Must be true for the owner to be changed")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetPlaceSummary {
+    #[schemars(description = "Path of the subtree to summarize, defaults to the whole place")]
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetTextChatSettings {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetTextChatSettings {
+    #[schemars(
+        description = "TextChatService.ChatVersion, TextChatService or LegacyChatService. Changing it requires confirm"
+    )]
+    chat_version: Option<String>,
+    #[schemars(description = "TextChatService.CreateDefaultTextChannels")]
+    create_default_text_channels: Option<bool>,
+    #[schemars(description = "TextChatService.CreateDefaultCommands, e.g. /whisper and /mute")]
+    create_default_commands: Option<bool>,
+    #[schemars(description = "ChatWindowConfiguration.Enabled")]
+    chat_window_enabled: Option<bool>,
+    #[schemars(description = "ChatWindowConfiguration.TextSize, a whole number from 1 to 100")]
+    chat_window_text_size: Option<f64>,
+    #[schemars(description = "ChatInputBarConfiguration.Enabled")]
+    chat_input_bar_enabled: Option<bool>,
+    #[schemars(description = "BubbleChatConfiguration.Enabled")]
+    bubble_chat_enabled: Option<bool>,
+    #[schemars(
+        description = "BubbleChatConfiguration.MaxDistance, studs beyond which bubbles are hidden, at least 0"
+    )]
+    bubble_max_distance: Option<f64>,
+    #[schemars(
+        description = "BubbleChatConfiguration.BubbleDuration, seconds a bubble stays up, at least 0"
+    )]
+    bubble_duration: Option<f64>,
+    #[schemars(description = "Names of TextChannels to create in TextChatService")]
+    add_channels: Option<Vec<String>>,
+    #[schemars(description = "Must be true to change chat_version")]
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct BenchmarkCode {
+    #[schemars(description = "Luau snippet to time, run once per iteration")]
+    code: String,
+    #[schemars(
+        description = "Luau run once before timing, globals it assigns are visible to code, e.g. test data"
+    )]
+    setup: Option<String>,
+    #[schemars(description = "Timed runs, 100 by default and at most 100000")]
+    iterations: Option<u32>,
+    #[schemars(description = "Untimed runs before timing starts, 10 by default")]
+    warmup: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetPlaceVersion {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct QuerySelector {
+    #[schemars(
+        description = "CSS-like selector, e.g. Workspace > Model Part[Anchored=true]. ClassName matches with IsA, * any instance, #Name the Name (quote names with spaces: #\"Spawn Location\"), .Tag a CollectionService tag, [Property=value] a property and [@Attribute=value] an attribute, with = != or *= (contains), or no operator to match set, non-false values. Values are true, false, numbers, enum item names, instance paths or strings. A space matches descendants, > children, and commas separate alternatives"
+    )]
+    selector: String,
+    #[schemars(
+        description = "Path of the instance to search, including the instance itself, defaults to game. Ancestors outside it still count for the selector"
+    )]
+    path: Option<String>,
+    #[schemars(description = "Maximum number of paths to return, 1 to 1000, defaults to 100")]
+    max_results: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct MatchSelector {
+    path: Option<String>,
+    #[schemars(skip)]
+    selectors: Vec<selector::Selector>,
+    max_results: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+enum ToolArgumentValues {
+    RunCode(RunCode),
+    InsertModel(InsertModel),
+    GetConsoleOutput(GetConsoleOutput),
+    StartStopPlay(StartStopPlay),
+    RunScriptInPlayMode(RunScriptInPlayMode),
+    GetStudioMode(GetStudioMode),
+    GetHumanoidConfig(GetHumanoidConfig),
+    SetHumanoidConfig(SetHumanoidConfig),
+    GetConfig(GetConfig),
+    SetConfig(SetConfig),
+    CaptureSelectionThumbnail(CaptureSelectionThumbnail),
+    FindBrokenAssets(FindBrokenAssets),
+    SnapIncrements(SnapIncrements),
+    GetRecentChanges(GetRecentChanges),
+    GetScriptSources(GetScriptSources),
+    SetScriptSource(SetScriptSource),
+    GetEnumValues(GetEnumValues),
+    GetOutputLog(GetOutputLog),
+    ResolveHandles(ResolveHandles),
+    GetCameraSettings(GetCameraSettings),
+    SetCameraSettings(SetCameraSettings),
+    GetPlaytestErrors(GetPlaytestErrors),
+    CreateFolder(CreateFolder),
+    PreviewTween(PreviewTween),
+    FindDisabledScripts(FindDisabledScripts),
+    EnableDisabledScripts(EnableDisabledScripts),
+    CollectScripts(CollectScripts),
+    ApplyScripts(ApplyScripts),
+    GetFonts(GetFonts),
+    SetFont(SetFont),
+    FindStructuralIssues(FindStructuralIssues),
+    GetSky(GetSky),
+    SetSky(SetSky),
+    SimulatePhysics(SimulatePhysics),
+    FindDuplicateAssets(FindDuplicateAssets),
+    GroupInstances(GroupInstances),
+    UngroupModel(UngroupModel),
+    ReconnectPlugin(ReconnectPlugin),
+    GetTeleportConfig(GetTeleportConfig),
+    ListContextActions(ListContextActions),
+    FireContextAction(FireContextAction),
+    FindBrokenWelds(FindBrokenWelds),
+    GetPivot(GetPivot),
+    SetPivot(SetPivot),
+    GetGameSettings(GetGameSettings),
+    SetGameSettings(SetGameSettings),
+    LayoutUi(LayoutUi),
+    GetPluginPermissions(GetPluginPermissions),
+    WatchPlaytestErrors(WatchPlaytestErrors),
+    OrganizeByClass(OrganizeByClass),
+    GetValueObjects(GetValueObjects),
+    SetValueObject(SetValueObject),
+    GetRequireGraph(GetRequireGraph),
+    GetStreamingSettings(GetStreamingSettings),
+    SetStreamingSettings(SetStreamingSettings),
+    GetBoundingBoxes(GetBoundingBoxes),
+    GetFidelity(GetFidelity),
+    SetFidelity(SetFidelity),
+    FindNameReferences(FindNameReferences),
+    FindScaleOutliers(FindScaleOutliers),
+    SetEffect(SetEffect),
+    GetWorkspaceThresholds(GetWorkspaceThresholds),
+    SetWorkspaceThresholds(SetWorkspaceThresholds),
+    GetCameraView(GetCameraView),
+    SetCameraView(SetCameraView),
+    FindDeprecatedApis(FindDeprecatedApis),
+    SetCollisionGroup(SetCollisionGroup),
+    FindMissingPrimaryParts(FindMissingPrimaryParts),
+    GetSelection(GetSelection),
+    RunCodeJson(RunCodeJson),
+    SetSelection(SetSelection),
+    GetPlaceIcons(GetPlaceIcons),
+    CreateInstance(CreateInstance),
+    FindOverlappingParts(FindOverlappingParts),
+    DeleteInstance(DeleteInstance),
+    GetLoadingGui(GetLoadingGui),
+    SetLoadingGui(SetLoadingGui),
+    GetProperties(GetProperties),
+    FindRunContextIssues(FindRunContextIssues),
+    SetProperty(SetProperty),
+    GetStarterDiff(GetStarterDiff),
+    Undo(Undo),
+    Redo(Redo),
+    RunBatch(RunBatch),
+    CheckUiDevices(CheckUiDevices),
+    GetSoundSettings(GetSoundSettings),
+    SetSoundSettings(SetSoundSettings),
+    FindUnusedScripts(FindUnusedScripts),
+    GetNetworkOwnership(GetNetworkOwnership),
+    SetNetworkOwnership(SetNetworkOwnership),
+    GetPlaceSummary(GetPlaceSummary),
+    GetTextChatSettings(GetTextChatSettings),
+    SetTextChatSettings(SetTextChatSettings),
+    BenchmarkCode(BenchmarkCode),
+    GetPlaceVersion(GetPlaceVersion),
+    MatchSelector(MatchSelector),
+}
+#[tool_router]
+impl RBXStudioServer {
+    pub fn new(state: PackedState, config: AppConfig) -> Self {
+        let mut tool_router = Self::tool_router();
+        for name in &config.disabled_tools {
+            if !tool_router.has_route(name) {
+                tracing::warn!("Ignoring unknown tool {name} in disabled tools");
+            }
+        }
+        for tool in tool_router.list_all() {
+            if Self::disabled_reason(&config, &tool).is_some() {
+                tool_router.remove_route(&tool.name);
+            }
+        }
+        Self {
+            state,
+            config: Arc::new(config),
+            tool_router,
+            session: Uuid::new_v4(),
+        }
+    }
+
+    /// Names of all tools before any are disabled, sorted
+    pub fn tool_names() -> Vec<String> {
+        let mut names: Vec<String> = Self::tool_router()
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn disabled_reason(config: &AppConfig, tool: &Tool) -> Option<&'static str> {
+        let read_only = tool
+            .annotations
+            .as_ref()
+            .and_then(|a| a.read_only_hint)
+            .unwrap_or(false);
+        let runs_code = RUNS_CODE.contains(&tool.name.as_ref());
+        if config
+            .disabled_tools
+            .iter()
+            .any(|name| *name == tool.name || (runs_code && name == "run_code"))
+        {
+            Some("disabled by configuration")
+        } else if config.read_only && !read_only {
+            Some("modifies the place, unavailable in read-only mode")
+        } else {
+            None
+        }
+    }
+
+    #[tool(
+        description = "Runs a command in Roblox Studio and returns the printed output. Can be used to both make changes and retrieve information. Long running code can call reportProgress(progress, total?, message?) to send progress notifications, and checkCancelled() to stop once the call is cancelled"
+    )]
+    async fn run_code(
+        &self,
+        Parameters(args): Parameters<RunCode>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::RunCode(args))
+            .await
+    }
+
+    #[tool(
+        description = "Inserts a model from the Roblox marketplace into the workspace. Returns the inserted model name, path and a handle for resolve_handles."
+    )]
+    async fn insert_model(
+        &self,
+        Parameters(args): Parameters<InsertModel>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::InsertModel(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the console output from Roblox Studio.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_console_output(
+        &self,
+        Parameters(args): Parameters<GetConsoleOutput>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetConsoleOutput(args))
+            .await
+    }
+
+    #[tool(
+        description = "Start or stop play mode or run the server, Don't enter run_server mode unless you are sure no client/player is needed."
+    )]
+    async fn start_stop_play(
+        &self,
+        Parameters(args): Parameters<StartStopPlay>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::StartStopPlay(args))
+            .await
+    }
+
+    #[tool(
+        description = "Run a script in play mode and automatically stop play after script finishes or timeout. Returns the output of the script.
+        Result format: { success: boolean, value: string, error: string, logs: { level: string, message: string, ts: number }[], errors: { level: string, message: string, ts: number }[], duration: number, isTimeout: boolean }.
+        - Prefer using start_stop_play tool instead run_script_in_play_mode, Only used run_script_in_play_mode to run one time unit test code on server datamodel.
+        - After calling run_script_in_play_mode, the datamodel status will be reset to stop mode.
+        - If It returns `StudioTestService: Previous call to start play session has not been completed`, call start_stop_play tool to stop play mode first then try it again."
+    )]
+    async fn run_script_in_play_mode(
+        &self,
+        Parameters(args): Parameters<RunScriptInPlayMode>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::RunScriptInPlayMode(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the current studio mode. Returns the studio mode. The result will be one of start_play, run_server, or stop.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_studio_mode(
+        &self,
+        Parameters(args): Parameters<GetStudioMode>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetStudioMode(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get WalkSpeed, JumpPower and HipHeight of a Humanoid or of the StarterPlayer character defaults. Returns the values as JSON.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_humanoid_config(
+        &self,
+        Parameters(args): Parameters<GetHumanoidConfig>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetHumanoidConfig(args))
+            .await
+    }
+
+    #[tool(
+        description = "Set WalkSpeed, JumpPower and/or HipHeight on a Humanoid or on the StarterPlayer character defaults. Omitted values are left unchanged. Returns the resolved values as JSON."
+    )]
+    async fn set_humanoid_config(
+        &self,
+        Parameters(args): Parameters<SetHumanoidConfig>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetHumanoidConfig(args))
+            .await
+    }
+
+    #[tool(
+        description = "List the commands this session queued for Roblox Studio that the plugin has not picked up yet, with ids, tool names and how long they have been waiting, and how many of its commands Studio is running. Useful to diagnose a stuck or disconnected Studio session.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_queued_commands(
+        &self,
+        Parameters(_args): Parameters<GetQueuedCommands>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let state = self.state.lock().await;
+        let session = Some(self.session);
+        let queued: Vec<Value> = state
+            .process_queue
+            .iter()
+            .filter(|command| command.session == session)
+            .map(|command| command.describe())
+            .collect();
+        let in_flight = state
+            .in_flight
+            .values()
+            .filter(|command_session| **command_session == session)
+            .count();
+        let report = json!({ "queued": queued, "in_flight": in_flight });
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Read a data-driven config as JSON, either the table returned by a ModuleScript (mode module) or an instance's attributes (mode attributes). Roblox datatypes are encoded as { type, value }.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_config(
+        &self,
+        Parameters(args): Parameters<GetConfig>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetConfig(args))
+            .await
+    }
+
+    #[tool(
+        description = "Merge values into a data-driven config, either rewriting a ModuleScript that returns a table (mode module) or setting an instance's attributes (mode attributes). Returns the resulting config as JSON."
+    )]
+    async fn set_config(
+        &self,
+        Parameters(args): Parameters<SetConfig>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetConfig(args))
+            .await
+    }
+
+    #[tool(
+        description = "Frame the camera on the currently selected parts/models, capture the viewport and return it as a PNG image, followed by the effective capture settings as JSON. Resolution, anti-aliasing and render quality trade detail against payload size. It moves the camera and changes render quality while capturing and restores both afterwards, so read-only mode does not offer it."
+    )]
+    async fn capture_selection_thumbnail(
+        &self,
+        Parameters(args): Parameters<CaptureSelectionThumbnail>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let response = match self
+            .send_to_studio(ToolArgumentValues::CaptureSelectionThumbnail(args))
+            .await?
+        {
+            Ok(response) => response,
+            Err(err) => return Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+        };
+        let png = serde_json::from_str::<screenshot::Capture>(&response)
+            .map_err(Report::from)
+            .and_then(|capture| Ok((capture.to_png_base64()?, capture.settings)));
+        match png {
+            Ok((png, settings)) => Ok(CallToolResult::success(vec![
+                Content::image(png, "image/png"),
+                Content::text(settings.to_string()),
+            ])),
+            Err(err) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Could not encode thumbnail: {err}"
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Scan a subtree for MeshId/TextureId/SoundId/Decal references that fail to load and return per-instance findings. Optionally clear or replace the broken references, which requires confirm set to true."
+    )]
+    async fn find_broken_assets(
+        &self,
+        Parameters(args): Parameters<FindBrokenAssets>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindBrokenAssets(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get Studio's move (studs) and rotate (degrees) snap increments so placed instances can align with the user's grid. Values passed are applied where Studio permits it; errors explain settings that cannot be changed."
+    )]
+    async fn snap_increments(
+        &self,
+        Parameters(args): Parameters<SnapIncrements>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SnapIncrements(args))
+            .await
+    }
+
+    #[tool(
+        description = "List instances added or edited since the plugin loaded, most recent first, with paths and timestamps. Property edits are tracked for instances while they are selected. Useful to review what was just changed.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_recent_changes(
+        &self,
+        Parameters(args): Parameters<GetRecentChanges>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetRecentChanges(args))
+            .await
+    }
+
+    #[tool(
+        description = "Read a JSON object from a file in the server's files directory and merge it into a config ModuleScript (mode module) or an instance's attributes (mode attributes). Returns the resulting config as JSON."
+    )]
+    async fn import_config_file(
+        &self,
+        Parameters(args): Parameters<ImportConfigFile>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let values = match self.read_json_file(&args.file) {
+            Ok(Value::Object(values)) => values,
+            Ok(_) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{} must contain a JSON object",
+                    args.file
+                ))]))
+            }
+            Err(err) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{err:#}"
+                ))]))
+            }
+        };
+        self.generic_tool_run(ToolArgumentValues::SetConfig(SetConfig {
+            path: args.path,
+            mode: args.mode,
+            values,
+            remove: None,
+            create: Some(true),
+        }))
+        .await
+    }
+
+    fn read_json_file(&self, file: &str) -> color_eyre::Result<Value> {
+        let path = self.config.files_path(file)?;
+        let contents = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Could not read {}", path.display()))?;
+        serde_json::from_str(&contents).wrap_err_with(|| format!("Could not parse {file} as JSON"))
+    }
+
+    #[tool(
+        description = "Return a unified diff between a script and another script or a proposed new source. Use it to show the user exactly what will change before editing a script.",
+        annotations(read_only_hint = true)
+    )]
+    async fn diff_scripts(
+        &self,
+        Parameters(args): Parameters<DiffScripts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut paths = vec![args.path.clone()];
+        match (&args.other_path, &args.source) {
+            (Some(other_path), None) => paths.push(other_path.clone()),
+            (None, Some(_)) => {}
+            _ => {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "Pass exactly one of other_path or source",
+                )]))
+            }
+        }
+
+        let mut sources = match self.fetch_script_sources(paths).await? {
+            Ok(sources) => sources,
+            Err(err) => return Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+        };
+        let (new_name, new_source) = match args.source {
+            Some(source) => (format!("{} (proposed)", args.path), source),
+            None => (
+                args.other_path.unwrap_or_default(),
+                sources.pop().unwrap_or_default(),
+            ),
+        };
+        let old_source = sources.swap_remove(0);
+        if old_source.len() > script_diff::MAX_SOURCE_BYTES
+            || new_source.len() > script_diff::MAX_SOURCE_BYTES
+        {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Sources larger than {} bytes can't be diffed",
+                script_diff::MAX_SOURCE_BYTES
+            ))]));
+        }
+
+        let diff = script_diff::unified_diff(&args.path, &old_source, &new_name, &new_source);
+        if diff.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No differences",
+            )]));
+        }
+        Ok(CallToolResult::success(vec![Content::text(diff)]))
+    }
+
+    /// Fetches script sources from the plugin, in the same order as `paths`
+    async fn fetch_script_sources(
+        &self,
+        paths: Vec<String>,
+    ) -> Result<Result<Vec<String>>, ErrorData> {
+        let count = paths.len();
+        let response = self
+            .send_to_studio(ToolArgumentValues::GetScriptSources(GetScriptSources {
+                paths,
+            }))
+            .await?;
+        Ok(response.and_then(|response| {
+            let sources: Vec<String> = serde_json::from_str(&response)?;
+            if sources.len() != count {
+                return Err(Report::from(eyre!(
+                    "Expected {count} sources from Studio, got {}",
+                    sources.len()
+                )));
+            }
+            Ok(sources)
+        }))
+    }
+
+    #[tool(
+        description = "Apply a unified diff to a script's source. Hunks are located by their context lines, so small line offsets are tolerated. Nothing is changed unless every hunk applies; failed hunks are returned so you can fetch the current source and retry. The edit can be undone in Studio."
+    )]
+    async fn apply_script_patch(
+        &self,
+        Parameters(args): Parameters<ApplyScriptPatch>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let source = match self.fetch_script_sources(vec![args.path.clone()]).await? {
+            Ok(mut sources) => sources.swap_remove(0),
+            Err(err) => return Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+        };
+        let result = match script_diff::apply_patch(&source, &args.patch) {
+            Ok(result) => result,
+            Err(err) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Could not parse patch: {err}"
+                ))]))
+            }
+        };
+        if !result.failed_hunks.is_empty() {
+            let report = json!({
+                "applied": false,
+                "failed_hunks": result.failed_hunks,
+            });
+            return Ok(CallToolResult::error(vec![Content::text(
+                report.to_string(),
+            )]));
+        }
+
+        self.generic_tool_run(ToolArgumentValues::SetScriptSource(SetScriptSource {
+            path: args.path,
+            source: result.source,
+        }))
+        .await
+    }
+
+    #[tool(
+        description = "List which tools this server has enabled and disabled (with the reason) and whether it runs in read-only or full mode. Check it before planning work that needs tools you can't see.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_capabilities(
+        &self,
+        Parameters(_args): Parameters<GetCapabilities>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let (enabled, disabled): (Vec<_>, Vec<_>) = Self::tool_router()
+            .list_all()
+            .into_iter()
+            .map(|tool| (Self::disabled_reason(&self.config, &tool), tool))
+            .partition(|(reason, _)| reason.is_none());
+        let report = json!({
+            "mode": if self.config.read_only { "read_only" } else { "full" },
+            "enabled": enabled.iter().map(|(_, tool)| &tool.name).collect::<Vec<_>>(),
+            "disabled": disabled
+                .iter()
+                .map(|(reason, tool)| json!({ "name": tool.name, "reason": reason }))
+                .collect::<Vec<_>>(),
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Get this server's effective configuration as JSON: version, plugin port, long poll timeout, command queue limit, mode, enabled and disabled tools, files directory and where logs are written. Useful when debugging a setup or filing a bug report.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_server_config(
+        &self,
+        Parameters(_args): Parameters<GetServerConfig>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let report = json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "host": self.config.host,
+            "port": self.config.port,
+            "poll_timeout_secs": self.config.poll_duration.as_secs(),
+            "command_timeout_secs": self.config.command_timeout.map(|timeout| timeout.as_secs()),
+            "connection_timeout_secs": self.config.connection_timeout.map(|window| window.as_secs()),
+            // The queue is unbounded, commands wait until the plugin picks them up
+            "queue_limit": Value::Null,
+            "mode": if self.config.read_only { "read_only" } else { "full" },
+            "enabled_tools": self
+                .tool_router
+                .list_all()
+                .into_iter()
+                .map(|tool| tool.name)
+                .collect::<Vec<_>>(),
+            "disabled_tools": self.config.disabled_tools,
+            "files_dir": self.config.files_dir,
+            "log_destination": match &self.config.log_file {
+                Some(path) => format!("stderr and {}", path.display()),
+                None => "stderr".to_owned(),
+            },
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Get this server process's own resource usage as JSON: resident and virtual memory in bytes, commands queued for Studio, tool calls waiting for a response and open MCP sessions. Useful to diagnose a server that grows over a long session.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_server_resources(
+        &self,
+        Parameters(_args): Parameters<GetServerResources>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let memory = process_memory();
+        let (queued, pending, http_sessions) = {
+            let state = self.state.lock().await;
+            (
+                state.process_queue.len(),
+                state.output_map.len(),
+                state.http_sessions.clone(),
+            )
+        };
+        let open_sessions = match http_sessions {
+            Some(manager) => manager.sessions.read().await.len(),
+            None => 1,
+        };
+        let report = json!({
+            "rss_bytes": memory.map(|(rss, _)| rss),
+            "virtual_memory_bytes": memory.map(|(_, virt)| virt),
+            "queued_commands": queued,
+            "pending_responses": pending,
+            "open_sessions": open_sessions,
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Get a quick health snapshot of this server as JSON: uptime in seconds, commands answered by the plugin since start, how many of them failed, the error rate and the commands answered in the last minute.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_server_stats(
+        &self,
+        Parameters(_args): Parameters<GetServerStats>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let report = {
+            let mut state = self.state.lock().await;
+            let stats = &mut state.stats;
+            let now = Instant::now();
+            stats.prune(now);
+            json!({
+                "uptime_secs": now.duration_since(stats.started_at).as_secs(),
+                "commands_processed": stats.completed,
+                "commands_failed": stats.failed,
+                "error_rate": if stats.completed == 0 {
+                    0.0
+                } else {
+                    stats.failed as f64 / stats.completed as f64
+                },
+                "commands_last_minute": stats.recent.len(),
+            })
+        };
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "List the valid items (names and numeric values) of an Enum, either by enum name or from an Enum-typed property of a class or instance. Use it to pick valid values before setting enum properties.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_enum_values(
+        &self,
+        Parameters(args): Parameters<GetEnumValues>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetEnumValues(args))
+            .await
+    }
+
+    #[tool(
+        description = "Write the Studio output captured by the plugin (up to 1MB, with timestamps and message types) to a file in the server's files directory and return its path. Use it for logs too large for get_console_output."
+    )]
+    async fn export_output_log(
+        &self,
+        Parameters(args): Parameters<ExportOutputLog>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let file = args.file.unwrap_or_else(|| {
+            let secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            format!("studio-output-{secs}.log")
+        });
+        let path = match self.config.files_path(&file) {
+            Ok(path) => path,
+            Err(err) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{err:#}"
+                ))]))
+            }
+        };
+        let log = match self
+            .send_to_studio(ToolArgumentValues::GetOutputLog(GetOutputLog {}))
+            .await?
+        {
+            Ok(log) => log,
+            Err(err) => return Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+        };
+
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, log.as_bytes()));
+        match written {
+            Ok(()) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Wrote {} bytes of output to {}",
+                log.len(),
+                path.display()
+            ))])),
+            Err(err) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Could not write {}: {err}",
+                path.display()
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Resolve instance handles returned by creation tools (e.g. insert_model) to the instances' current full paths, following any renames or reparents since. Handles expire when the plugin disconnects.",
+        annotations(read_only_hint = true)
+    )]
+    async fn resolve_handles(
+        &self,
+        Parameters(args): Parameters<ResolveHandles>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::ResolveHandles(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the user's Studio camera navigation settings (camera speed, shift speed and mouse wheel speed) as JSON. Settings Studio doesn't expose to plugins are listed under unavailable.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_camera_settings(
+        &self,
+        Parameters(args): Parameters<GetCameraSettings>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetCameraSettings(args))
+            .await
+    }
+
+    #[tool(
+        description = "Set the user's Studio camera navigation settings. Only change these when the user asks to; omitted values are left unchanged. Returns the resolved values as JSON."
+    )]
+    async fn set_camera_settings(
+        &self,
+        Parameters(args): Parameters<SetCameraSettings>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetCameraSettings(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the errors raised during the most recent playtest as JSON, with the message, the script path and line number where they could be parsed, and the stack trace. Capped at 100 errors; cleared when a new playtest starts.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_playtest_errors(
+        &self,
+        Parameters(args): Parameters<GetPlaytestErrors>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetPlaytestErrors(args))
+            .await
+    }
+
+    #[tool(
+        description = "Create a named Folder under a parent (or reuse an existing one) and optionally move instances into it, as a single undo step. Returns the folder's path and handle and the new paths of the moved instances as JSON."
+    )]
+    async fn create_folder(
+        &self,
+        Parameters(args): Parameters<CreateFolder>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::CreateFolder(args))
+            .await
+    }
+
+    #[tool(
+        description = "Preview a TweenService animation of one property on an instance in edit mode and return when it finishes. By default the original value is restored afterwards. Not available while a playtest is running. Returns the value reached as JSON."
+    )]
+    async fn preview_tween(
+        &self,
+        Parameters(args): Parameters<PreviewTween>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::PreviewTween(args))
+            .await
+    }
+
+    #[tool(
+        description = "List the paths of Scripts and LocalScripts that are disabled (Enabled = false) under a subtree as JSON. Disabled scripts are a common reason code doesn't run.",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_disabled_scripts(
+        &self,
+        Parameters(args): Parameters<FindDisabledScripts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindDisabledScripts(args))
+            .await
+    }
+
+    #[tool(
+        description = "Enable the given scripts, or every disabled script under a subtree, as a single undo step. Requires confirm set to true. Returns the paths of the enabled scripts as JSON."
+    )]
+    async fn enable_disabled_scripts(
+        &self,
+        Parameters(args): Parameters<EnableDisabledScripts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::EnableDisabledScripts(args))
+            .await
+    }
+
+    #[tool(
+        description = "Export every Script, LocalScript and ModuleScript in a subtree to a directory in the server's files directory, mirroring the hierarchy as folders (Name.server.luau, Name.client.luau, Name.luau, and Name/init.*.luau for scripts with children). Returns the number of files written, the directory and any scripts skipped because of name collisions as JSON."
+    )]
+    async fn export_scripts(
+        &self,
+        Parameters(args): Parameters<ExportScripts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let directory = args.directory.as_deref().unwrap_or("scripts");
+        let dir = match self.config.files_path(directory) {
+            Ok(dir) => dir,
+            Err(err) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{err:#}"
+                ))]))
+            }
+        };
+        let response = match self
+            .send_to_studio(ToolArgumentValues::CollectScripts(CollectScripts {
+                path: args.path,
+            }))
+            .await?
+        {
+            Ok(response) => response,
+            Err(err) => return Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+        };
+        let scripts: Vec<script_files::ScriptEntry> = match serde_json::from_str(&response) {
+            Ok(scripts) => scripts,
+            Err(err) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Unexpected response from Studio: {err}"
+                ))]))
+            }
+        };
+
+        let (files, skipped) = script_files::layout(&scripts);
+        match script_files::write_all(&dir, &files) {
+            Ok(written) => Ok(CallToolResult::success(vec![Content::text(
+                json!({
+                    "written": written,
+                    "directory": dir.display().to_string(),
+                    "skipped": skipped,
+                })
+                .to_string(),
+            )])),
+            Err(err) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Could not write scripts to {}: {err}",
+                dir.display()
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Import .lua and .luau files from a directory in the server's files directory into the place under a target instance, as a single undo step. Uses the export_scripts layout: folders become Folders (or existing instances), Name.server.luau a Script, Name.client.luau a LocalScript, other files ModuleScripts and init files the folder's own script. Existing scripts are updated. Returns created and updated counts and failures as JSON."
+    )]
+    async fn import_scripts(
+        &self,
+        Parameters(args): Parameters<ImportScripts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let directory = args.directory.as_deref().unwrap_or("scripts");
+        let scripts = match self.config.files_path(directory).and_then(|dir| {
+            script_files::read_all(&dir)
+                .wrap_err_with(|| format!("Could not read scripts from {}", dir.display()))
+        }) {
+            Ok(scripts) => scripts,
+            Err(err) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{err:#}"
+                ))]))
+            }
+        };
+        if scripts.is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "No .lua or .luau files found in {directory}"
+            ))]));
+        }
+        self.generic_tool_run(ToolArgumentValues::ApplyScripts(ApplyScripts {
+            path: args.path,
+            scripts,
+        }))
+        .await
+    }
+
+    #[tool(
+        description = "List the available Enum.Font names with their font family assets, plus the valid FontWeight and FontStyle names, as JSON.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_fonts(
+        &self,
+        Parameters(args): Parameters<GetFonts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetFonts(args))
+            .await
+    }
+
+    #[tool(
+        description = "Set the FontFace of TextLabels, TextButtons or TextBoxes from a font name or family asset with an optional weight and style. Returns the resolved FontFace per instance as JSON."
+    )]
+    async fn set_font(
+        &self,
+        Parameters(args): Parameters<SetFont>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetFont(args))
+            .await
+    }
+
+    #[tool(
+        description = "Scan a subtree for structural problems: ObjectValues referencing themselves, each other in a cycle or instances no longer in the place, service classes nested below other instances, Terrain outside Workspace and Humanoids parented to Humanoids. Returns findings with paths and descriptions as JSON.",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_structural_issues(
+        &self,
+        Parameters(args): Parameters<FindStructuralIssues>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindStructuralIssues(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the skybox textures, sun and moon settings and star count of the Sky under Lighting as JSON.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_sky(
+        &self,
+        Parameters(args): Parameters<GetSky>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetSky(args))
+            .await
+    }
+
+    #[tool(
+        description = "Configure the Sky under Lighting, creating it if needed. Asset ids may be rbxassetid:// ids, asset URLs or bare numbers. Omitted values are left unchanged. Returns the resolved values as JSON."
+    )]
+    async fn set_sky(
+        &self,
+        Parameters(args): Parameters<SetSky>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetSky(args))
+            .await
+    }
+
+    #[tool(
+        description = "Simulate physics briefly in a server-only test run and report, per part, the start and end positions, displacement, peak speed and final velocities as JSON. Useful to diagnose parts that fall, drift or fly apart. Not available while a playtest is running; the place is left unchanged."
+    )]
+    async fn simulate_physics(
+        &self,
+        Parameters(args): Parameters<SimulatePhysics>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SimulatePhysics(args))
+            .await
+    }
+
+    #[tool(
+        description = "Find meshes (MeshParts and SpecialMeshes sharing MeshId and TextureId), SurfaceAppearances with identical maps and identical MaterialVariants, returned as JSON groups of paths. Optionally consolidates duplicate MaterialVariants, which requires confirm set to true."
+    )]
+    async fn find_duplicate_assets(
+        &self,
+        Parameters(args): Parameters<FindDuplicateAssets>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindDuplicateAssets(args))
+            .await
+    }
+
+    #[tool(
+        description = "Group instances (or the current Studio selection) into a new Model under the first instance's parent, like Studio's Group command. Returns the Model's path and handle as JSON."
+    )]
+    async fn group_instances(
+        &self,
+        Parameters(args): Parameters<GroupInstances>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GroupInstances(args))
+            .await
+    }
+
+    #[tool(
+        description = "Ungroup a Model, moving its children into its parent and removing it, like Studio's Ungroup command. Returns the new paths of the children as JSON."
+    )]
+    async fn ungroup_model(
+        &self,
+        Parameters(args): Parameters<UngroupModel>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::UngroupModel(args))
+            .await
+    }
+
+    #[tool(
+        description = "Ask the Studio plugin to drop its connection and reconnect to the server, e.g. after the server was restarted with different settings. Reports whether the plugin acknowledged the request."
+    )]
+    async fn reconnect_plugin(
+        &self,
+        Parameters(args): Parameters<ReconnectPlugin>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let sent = self
+            .send_to_studio_within(
+                ToolArgumentValues::ReconnectPlugin(args),
+                Some(RECONNECT_ACK_TIMEOUT),
+            )
+            .await?;
+        let report = match sent {
+            Ok(_) => json!({ "acknowledged": true }),
+            Err(err) => json!({ "acknowledged": false, "error": err.to_string() }),
+        };
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Get the teleport setup of the experience as JSON: the current place and experience ids, the places in the experience and the script lines that call TeleportService APIs (TeleportAsync, ReserveServer, TeleportOptions, teleport data and GUIs). Never teleports or reserves servers.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_teleport_config(
+        &self,
+        Parameters(args): Parameters<GetTeleportConfig>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetTeleportConfig(args))
+            .await
+    }
+
+    #[tool(
+        description = "List the ContextActionService actions bound on the client of the running playtest as JSON, with priority, stack order, input types and touch button settings. Requires a playtest started with start_stop_play.",
+        annotations(read_only_hint = true)
+    )]
+    async fn list_context_actions(
+        &self,
+        Parameters(args): Parameters<ListContextActions>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::ListContextActions(args))
+            .await
+    }
+
+    #[tool(
+        description = "Simulate a ContextActionService action on the client of the running playtest by calling its handler with the given input states. Requires confirm set to true. Returns what the handler returned per state as JSON."
+    )]
+    async fn fire_context_action(
+        &self,
+        Parameters(args): Parameters<FireContextAction>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FireContextAction(args))
+            .await
+    }
+
+    #[tool(
+        description = "Find welds and joints missing one of their parts, and unanchored parts that are not connected to the rest of their Model (its PrimaryPart, or its largest assembly) and would fall off when the game runs. Optionally welds detached parts back with WeldConstraints, which requires confirm set to true. Returns findings and created welds as JSON."
+    )]
+    async fn find_broken_welds(
+        &self,
+        Parameters(args): Parameters<FindBrokenWelds>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindBrokenWelds(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the pivot of a BasePart or Model in world space and its PivotOffset as JSON. CFrames are encoded as {type: CFrame, value: [x, y, z, R00, R01, R02, R10, R11, R12, R20, R21, R22]}.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_pivot(
+        &self,
+        Parameters(args): Parameters<GetPivot>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetPivot(args))
+            .await
+    }
+
+    #[tool(
+        description = "Change the pivot of a BasePart or Model, so rotations and PivotTo behave as expected. Pass exactly one of pivot_offset, world_pivot (moves the pivot, not the geometry) or move_to (moves the object by its pivot). Returns the resolved pivot as JSON."
+    )]
+    async fn set_pivot(
+        &self,
+        Parameters(args): Parameters<SetPivot>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetPivot(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the experience settings stored in the place (gravity, character loading and respawn, camera and movement defaults, HttpEnabled, ids) as JSON, which of them can be changed from Studio, and the settings that can only be changed from the Creator Dashboard.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_game_settings(
+        &self,
+        Parameters(args): Parameters<GetGameSettings>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetGameSettings(args))
+            .await
+    }
+
+    #[tool(
+        description = "Change writable experience settings listed by get_game_settings. All values are validated before anything changes. Requires confirm set to true. Returns the resolved settings as JSON."
+    )]
+    async fn set_game_settings(
+        &self,
+        Parameters(args): Parameters<SetGameSettings>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetGameSettings(args))
+            .await
+    }
+
+    #[tool(
+        description = "Compute where every GuiObject in a UI subtree lands at a target resolution (accounting for scale, offset, AnchorPoint and the top bar inset) and report pixel positions and sizes, UDim2 Position and Size and which elements overflow the screen, as JSON. Elements positioned by UI layouts or constraints are flagged as estimates. Optionally nudges overflowing elements on screen, which requires confirm set to true."
+    )]
+    async fn layout_ui(
+        &self,
+        Parameters(args): Parameters<LayoutUi>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::LayoutUi(args))
+            .await
+    }
+
+    #[tool(
+        description = "Report which Studio permissions the MCP plugin currently has (script injection, HTTP to the server) and whether HttpService is enabled for the experience's scripts, as JSON booleans. Check it before tools that create or edit scripts.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_plugin_permissions(
+        &self,
+        Parameters(args): Parameters<GetPluginPermissions>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetPluginPermissions(args))
+            .await
+    }
+
+    #[tool(
+        description = "Watch the running playtest for new errors. Each new error-level output message is sent as a progress notification (when the request has a progress token) as soon as it is seen. Returns the errors as JSON once the playtest ends, the timeout elapses or the request is cancelled. Requires a playtest started with start_stop_play.",
+        annotations(read_only_hint = true)
+    )]
+    async fn watch_playtest_errors(
+        &self,
+        Parameters(args): Parameters<WatchPlaytestErrors>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let timeout = args
+            .timeout_secs
+            .unwrap_or(WATCH_DEFAULT_SECS)
+            .clamp(1, WATCH_MAX_SECS);
+        let deadline = Instant::now() + Duration::from_secs(timeout);
+        let progress_token = context.meta.get_progress_token();
+        let mut cursor = None;
+        let mut errors: Vec<Value> = Vec::new();
+
+        let ended_by = loop {
+            let poll = ToolArgumentValues::WatchPlaytestErrors(WatchPlaytestErrors {
+                timeout_secs: None,
+                cursor,
+            });
+            let response = tokio::select! {
+                response = self.send_to_studio(poll) => response?,
+                _ = context.ct.cancelled() => break "cancelled",
+                _ = tokio::time::sleep_until(deadline) => break "timeout",
+            };
+            let status: Value = match response {
+                Ok(response) => serde_json::from_str(&response).map_err(|e| {
+                    ErrorData::internal_error(format!("Invalid watch response: {e}"), None)
+                })?,
+                Err(err) => return Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+            };
+            let running = status["running"].as_bool().unwrap_or(false);
+            if cursor.is_none() && !running {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "No playtest is running, start one with start_stop_play first",
+                )]));
+            }
+
+            for error in status["errors"].as_array().into_iter().flatten() {
+                errors.push(error.clone());
+                if let Some(progress_token) = &progress_token {
+                    let notified = context
+                        .peer
+                        .notify_progress(ProgressNotificationParam {
+                            progress_token: progress_token.clone(),
+                            progress: errors.len() as f64,
+                            total: None,
+                            message: Some(error.to_string()),
+                        })
+                        .await;
+                    if let Err(e) = notified {
+                        tracing::warn!("Unable to send error notification: {e}");
+                    }
+                }
+            }
+            cursor = status["cursor"].as_u64().or(cursor);
+
+            if !running {
+                break "playtest_ended";
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(WATCH_POLL_INTERVAL) => {}
+                _ = context.ct.cancelled() => break "cancelled",
+                _ = tokio::time::sleep_until(deadline) => break "timeout",
+            }
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(
+            json!({ "ended_by": ended_by, "errors": errors }).to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Move instances out of a messy container (game.Workspace by default) into services based on class-to-destination rules, as a single undo step. Instances whose name is already taken at the destination are skipped. Requires confirm set to true. Returns the moves and skipped instances as JSON."
+    )]
+    async fn organize_by_class(
+        &self,
+        Parameters(args): Parameters<OrganizeByClass>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::OrganizeByClass(args))
+            .await
+    }
+
+    #[tool(
+        description = "Read the Value of a ValueBase instance (IntValue, StringValue, CFrameValue, ObjectValue, ...) or of every ValueBase below a container, as JSON with path, class_name, type and value. ObjectValue references are returned as paths and datatypes use the {type, value} encoding.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_value_objects(
+        &self,
+        Parameters(args): Parameters<GetValueObjects>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetValueObjects(args))
+            .await
+    }
+
+    #[tool(
+        description = "Set the Value of a ValueBase instance as an undo step. The value is checked against the class, e.g. IntValue only takes integers. Returns the resolved value as JSON."
+    )]
+    async fn set_value_object(
+        &self,
+        Parameters(args): Parameters<SetValueObject>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetValueObject(args))
+            .await
+    }
+
+    #[tool(
+        description = "Statically build the require graph of the scripts under a subtree and report circular requires between ModuleScripts. Returns JSON with the modules each script requires, the cycles found as lists of paths, and requires that can't be resolved without running code (dynamic expressions, asset ids). Scans up to 2000 scripts.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_require_graph(
+        &self,
+        Parameters(args): Parameters<GetRequireGraph>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetRequireGraph(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the Workspace instance streaming settings as JSON: streaming_enabled, target_radius, min_radius, pause_mode, integrity_mode and stream_out_behavior. Enums are returned as item names.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_streaming_settings(
+        &self,
+        Parameters(args): Parameters<GetStreamingSettings>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetStreamingSettings(args))
+            .await
+    }
+
+    #[tool(
+        description = "Change Workspace instance streaming settings as a single undo step. Radii are validated against each other and enums take item names. Only the given settings change. Returns the resolved settings as JSON."
+    )]
+    async fn set_streaming_settings(
+        &self,
+        Parameters(args): Parameters<SetStreamingSettings>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetStreamingSettings(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the world axis-aligned bounding boxes of the parts in a subtree as JSON, with per-part min and max corners and the aggregate box of all parts. A lightweight alternative to screenshots for reasoning about spatial layout. Lists are capped and truncated is set when parts were left out.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_bounding_boxes(
+        &self,
+        Parameters(args): Parameters<GetBoundingBoxes>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetBoundingBoxes(args))
+            .await
+    }
+
+    #[tool(
+        description = "Count the RenderFidelity and CollisionFidelity of MeshParts and unions and the LevelOfDetail of Models in the given instances (or the Studio selection) and their descendants, as JSON counts per enum item.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_fidelity(
+        &self,
+        Parameters(args): Parameters<GetFidelity>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetFidelity(args))
+            .await
+    }
+
+    #[tool(
+        description = "Set RenderFidelity, CollisionFidelity and/or LevelOfDetail on every applicable instance in the given instances (or the Studio selection) and their descendants, as a single undo step. Returns how many instances changed per property and the new counts as JSON."
+    )]
+    async fn set_fidelity(
+        &self,
+        Parameters(args): Parameters<SetFidelity>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetFidelity(args))
+            .await
+    }
+
+    #[tool(
+        description = "Best-effort scan for string literals in scripts that match the names of instances under a subtree, such as WaitForChild(\"Door\") or Folder[\"Door\"], which would break if those instances were renamed. Returns JSON references with script path, line, the lookup method if any and the matching instance paths. Heuristic: matches may be unrelated strings and computed names are missed.",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_name_references(
+        &self,
+        Parameters(args): Parameters<FindNameReferences>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindNameReferences(args))
+            .await
+    }
+
+    #[tool(
+        description = "Find parts whose scale (largest dimension) is wildly out of line with the rest of a model, usually parts imported in the wrong unit. Returns JSON findings with paths, sizes and the ratio to the median part. Optionally resizes the outliers to the median scale, which requires confirm set to true."
+    )]
+    async fn find_scale_outliers(
+        &self,
+        Parameters(args): Parameters<FindScaleOutliers>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindScaleOutliers(args))
+            .await
+    }
+
+    #[tool(
+        description = "Create a ParticleEmitter, Beam or Trail on a part, or configure an existing one, as a single undo step. New beams and trails get two attachments on the part unless Attachment0 and Attachment1 are given. Particle emitters can be previewed in edit mode by emitting particles. Returns the effect's path, handle and properties as JSON."
+    )]
+    async fn set_effect(
+        &self,
+        Parameters(args): Parameters<SetEffect>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetEffect(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get global Workspace thresholds as JSON: fallen_parts_destroy_height and air_density.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_workspace_thresholds(
+        &self,
+        Parameters(args): Parameters<GetWorkspaceThresholds>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetWorkspaceThresholds(args))
+            .await
+    }
+
+    #[tool(
+        description = "Set global Workspace thresholds (FallenPartsDestroyHeight, AirDensity) as a single undo step. Values are range checked before anything changes. Returns the resolved thresholds as JSON."
+    )]
+    async fn set_workspace_thresholds(
+        &self,
+        Parameters(args): Parameters<SetWorkspaceThresholds>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetWorkspaceThresholds(args))
+            .await
+    }
+
+    #[tool(
+        description = "Save a named camera bookmark for this session, from the current Studio camera or a given CFrame. Bookmarks are kept by the server and forgotten when the session ends. Returns all bookmarks as JSON."
+    )]
+    async fn save_camera_bookmark(
+        &self,
+        Parameters(args): Parameters<SaveCameraBookmark>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let view = match args.cframe {
+            Some(cframe) => match encode_cframe(&cframe) {
+                Some(cframe) => json!({ "cframe": cframe, "focus": Value::Null }),
+                None => {
+                    return Ok(CallToolResult::error(vec![Content::text(
+                        "cframe must be [x, y, z] or the 12 CFrame components",
+                    )]))
+                }
+            },
+            None => match self
+                .send_to_studio(ToolArgumentValues::GetCameraView(GetCameraView {}))
+                .await?
+            {
+                Ok(response) => serde_json::from_str(&response).map_err(|e| {
+                    ErrorData::internal_error(format!("Invalid camera view: {e}"), None)
+                })?,
+                Err(err) => return Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+            },
+        };
+        let mut state = self.state.lock().await;
+        state.camera_bookmarks.insert(args.name, view);
+        Ok(CallToolResult::success(vec![Content::text(
+            bookmark_list(&state.camera_bookmarks).to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Move the Studio camera to a bookmark saved with save_camera_bookmark. Returns the resulting camera CFrame and focus as JSON."
+    )]
+    async fn go_to_camera_bookmark(
+        &self,
+        Parameters(args): Parameters<GoToCameraBookmark>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let view = self
+            .state
+            .lock()
+            .await
+            .camera_bookmarks
+            .get(&args.name)
+            .cloned();
+        let Some(view) = view else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "No camera bookmark named {}",
+                args.name
+            ))]));
+        };
+        self.generic_tool_run(ToolArgumentValues::SetCameraView(SetCameraView {
+            cframe: view["cframe"].clone(),
+            focus: Some(view["focus"].clone()).filter(|focus| !focus.is_null()),
+        }))
+        .await
+    }
+
+    #[tool(
+        description = "List the camera bookmarks saved in this session as JSON, with each bookmark's camera CFrame and focus.",
+        annotations(read_only_hint = true)
+    )]
+    async fn list_camera_bookmarks(
+        &self,
+        Parameters(_args): Parameters<ListCameraBookmarks>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let state = self.state.lock().await;
+        Ok(CallToolResult::success(vec![Content::text(
+            bookmark_list(&state.camera_bookmarks).to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Remove a camera bookmark saved in this session. Returns the remaining bookmarks as JSON."
+    )]
+    async fn remove_camera_bookmark(
+        &self,
+        Parameters(args): Parameters<RemoveCameraBookmark>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut state = self.state.lock().await;
+        if state.camera_bookmarks.remove(&args.name).is_none() {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "No camera bookmark named {}",
+                args.name
+            ))]));
+        }
+        Ok(CallToolResult::success(vec![Content::text(
+            bookmark_list(&state.camera_bookmarks).to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Scan scripts for deprecated API usage such as wait(), spawn(), delay(), :remove() and :SetPrimaryPartCFrame(), using a built-in denylist that can be replaced or trimmed. Returns JSON findings with script path, line, the API and its modern replacement when known.",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_deprecated_apis(
+        &self,
+        Parameters(args): Parameters<FindDeprecatedApis>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindDeprecatedApis(args))
+            .await
+    }
+
+    #[tool(
+        description = "Assign a registered PhysicsService collision group to the given parts (or the Studio selection) and all parts inside them, as a single undo step. Fails if the group doesn't exist. Returns how many parts were found and changed as JSON."
+    )]
+    async fn set_collision_group(
+        &self,
+        Parameters(args): Parameters<SetCollisionGroup>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetCollisionGroup(args))
+            .await
+    }
+
+    #[tool(
+        description = "Find Models without a PrimaryPart, which breaks SetPrimaryPartCFrame and makes pivots depend on the bounding box. Each finding suggests a candidate: a HumanoidRootPart or similarly named root part, otherwise the Model's largest part. Optionally sets the candidates, which requires confirm set to true."
+    )]
+    async fn find_missing_primary_parts(
+        &self,
+        Parameters(args): Parameters<FindMissingPrimaryParts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindMissingPrimaryParts(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the instances currently selected in Studio as a JSON array of their paths, class names and names. Returns an empty array when nothing is selected.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_selection(
+        &self,
+        Parameters(args): Parameters<GetSelection>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetSelection(args))
+            .await
+    }
+
+    #[tool(
+        description = "Runs code in Roblox Studio and returns its return value as JSON along with an inferred schema: objects list their fields and types, arrays the merged schema of their items. Instances become {type: \"Instance\", value: path}, functions and other values JSON can't hold become {type, value} markers, and cycles are marked. Returns {result, schema, truncated}, where truncated is set when max_depth or max_items cut the result."
+    )]
+    async fn run_code_json(
+        &self,
+        Parameters(args): Parameters<RunCodeJson>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::RunCodeJson(args))
+            .await
+    }
+
+    #[tool(
+        description = "Replace the Studio selection with the instances at the given paths. Paths that don't resolve are skipped instead of failing the call. Returns the selected and unresolved paths as JSON."
+    )]
+    async fn set_selection(
+        &self,
+        Parameters(args): Parameters<SetSelection>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetSelection(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the published place's icon asset id and content id, read from its product info, as JSON. Icons and thumbnails can't be changed from Studio, so the result also lists what has to be done on the Creator Dashboard and links to it. Reports published false for places that were never published.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_place_icons(
+        &self,
+        Parameters(args): Parameters<GetPlaceIcons>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetPlaceIcons(args))
+            .await
+    }
+
+    #[tool(
+        description = "Create an instance of a class under a parent and set its properties. Nothing is created when the class is unknown or a property is invalid. Returns the new instance's path, name and a handle for resolve_handles as JSON."
+    )]
+    async fn create_instance(
+        &self,
+        Parameters(args): Parameters<CreateInstance>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::CreateInstance(args))
+            .await
+    }
+
+    #[tool(
+        description = "Find pairs of parts whose geometry intersects among the given instances (or the Studio selection) and their descendants, to spot Z-fighting and unintended overlaps. Parts are only checked against each other, and parts outside the Workspace or with CanQuery disabled are skipped. Returns the overlapping pairs as arrays of two paths, up to 200 pairs, as JSON.",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_overlapping_parts(
+        &self,
+        Parameters(args): Parameters<FindOverlappingParts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindOverlappingParts(args))
+            .await
+    }
+
+    #[tool(
+        description = "Delete the instances at the given paths as a single undo step. Paths that don't resolve or can't be deleted are reported without stopping the others. Returns each path with whether it was deleted and why not as JSON."
+    )]
+    async fn delete_instance(
+        &self,
+        Parameters(args): Parameters<DeleteInstance>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::DeleteInstance(args))
+            .await
+    }
+
+    #[tool(
+        description = "Get the loading and teleport GUI setup as JSON: StarterGui settings, GUIs in ReplicatedFirst, the loader script installed by set_loading_gui, and where scripts call RemoveDefaultLoadingScreen or SetTeleportGui. Also lists what can't be changed or tested from Studio.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_loading_gui(
+        &self,
+        Parameters(args): Parameters<GetLoadingGui>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetLoadingGui(args))
+            .await
+    }
+
+    #[tool(
+        description = "Change StarterGui settings and/or install a ScreenGui as the loading screen, optionally also as the teleport GUI. Installing adds a LocalScript to ReplicatedFirst and requires confirm set to true. Nothing teleports. Returns the resulting setup like get_loading_gui."
+    )]
+    async fn set_loading_gui(
+        &self,
+        Parameters(args): Parameters<SetLoadingGui>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetLoadingGui(args))
+            .await
+    }
+
+    #[tool(
+        description = "Read properties of the instance at a path. Returns JSON with the path, class_name, properties by name and errors for names that aren't readable properties. Values use the {type, value} encoding described in the README, e.g. {\"type\": \"Vector3\", \"value\": [0, 5, 0]}, enum items read as {\"type\": \"EnumItem\", \"value\": \"Enum.Material.Plastic\"} and instances as their path. Fails if the path doesn't resolve.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_properties(
+        &self,
+        Parameters(args): Parameters<GetProperties>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetProperties(args))
+            .await
+    }
+
+    #[tool(
+        description = "List Scripts and LocalScripts with their RunContext (Legacy, Server or Client; LocalScripts count as Client) and flag likely misconfigurations: client scripts in ServerScriptService or ServerStorage, LocalScripts in the Workspace outside characters, Server scripts in replicated services, and legacy Scripts in ReplicatedStorage or ReplicatedFirst. Returns counts per RunContext, the scripts and findings with paths and the issue as JSON.",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_run_context_issues(
+        &self,
+        Parameters(args): Parameters<FindRunContextIssues>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindRunContextIssues(args))
+            .await
+    }
+
+    #[tool(
+        description = "Set one property of the instance at a path as a single undo step. The value is decoded to the property's type and the call fails on a mismatch, e.g. a string for a number property. Returns the property's new value as JSON."
+    )]
+    async fn set_property(
+        &self,
+        Parameters(args): Parameters<SetProperty>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetProperty(args))
+            .await
+    }
+
+    #[tool(
+        description = "Make this server answer HTTP requests to a path with a canned response, so code using HttpService can be tested deterministically. Point the code under test at the returned url; HttpService.HttpEnabled must be on for game scripts. Any method is answered. Mocks last until they are unregistered or the server exits. Returns the url and all registered mocks with their hit counts as JSON."
+    )]
+    async fn register_http_mock(
+        &self,
+        Parameters(args): Parameters<RegisterHttpMock>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let status = match StatusCode::from_u16(args.status.unwrap_or(200)) {
+            Ok(status) => status,
+            Err(_) => {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "status must be an HTTP status code between 100 and 999",
+                )]))
+            }
+        };
+        let path = args.path.trim_start_matches('/').to_owned();
+        if path.is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "path must not be empty",
+            )]));
+        }
+        let (content_type, body) = match args.body {
+            Value::String(body) => ("text/plain", body),
+            body => ("application/json", body.to_string()),
+        };
+        let mut state = self.state.lock().await;
+        if state.proxied {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Another server instance owns the plugin port, so this instance can't serve mocks. Register them from that instance's session",
+            )]));
+        }
+        let url = self.mock_url(&path);
+        state.http_mocks.insert(
+            path,
+            HttpMock {
+                status,
+                content_type: args.content_type.unwrap_or(content_type.to_owned()),
+                body,
+                hits: 0,
+            },
+        );
+        Ok(CallToolResult::success(vec![Content::text(
+            json!({ "url": url, "mocks": self.mock_list(&state.http_mocks) }).to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Remove an HTTP mock registered with register_http_mock, or all of them. Returns the remaining mocks with their hit counts as JSON."
+    )]
+    async fn unregister_http_mock(
+        &self,
+        Parameters(args): Parameters<UnregisterHttpMock>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut state = self.state.lock().await;
+        match args.path {
+            Some(path) => {
+                if state
+                    .http_mocks
+                    .remove(path.trim_start_matches('/'))
+                    .is_none()
+                {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "No HTTP mock is registered for {path}"
+                    ))]));
+                }
+            }
+            None => state.http_mocks.clear(),
+        }
+        Ok(CallToolResult::success(vec![Content::text(
+            json!({ "mocks": self.mock_list(&state.http_mocks) }).to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "During a playtest started with start_stop_play, compare StarterGui with the player's PlayerGui and StarterPack with their Backpack and held tools, to debug GUIs or tools that didn't show up. Returns findings as JSON, each with the container, the path relative to it and an issue: missing, class_mismatch, disabled (a GUI with Enabled off) or extra (added by a script or Roblox). Fails outside of a playtest.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_starter_diff(
+        &self,
+        Parameters(args): Parameters<GetStarterDiff>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetStarterDiff(args))
+            .await
+    }
+
+    #[tool(
+        description = "Undo the last change in Studio's undo history, like Ctrl+Z. Each tool call that changes the place is one undo step labelled \"MCP: ...\". Returns JSON with undone (false when there was nothing to undo), the name of the waypoint that was undone and the one the next undo would revert."
+    )]
+    async fn undo(&self, Parameters(args): Parameters<Undo>) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::Undo(args)).await
+    }
+
+    #[tool(
+        description = "Redo the last undone change in Studio's undo history, like Ctrl+Y. Returns JSON with redone (false when there was nothing to redo), the name of the waypoint that was redone and the one the next redo would restore."
+    )]
+    async fn redo(&self, Parameters(args): Parameters<Redo>) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::Redo(args)).await
+    }
+
+    #[tool(
+        description = "Start recording the tool calls made in this session as a named macro, to replay repetitive setup later with replay_macro. Successful calls of tools that are forwarded to Studio as they are get recorded, server-side tools like this one aren't. Stop with stop_macro_recording."
+    )]
+    async fn start_macro_recording(
+        &self,
+        Parameters(args): Parameters<StartMacroRecording>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let valid = !args.name.is_empty()
+            && args
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Macro names may only contain letters, digits, - and _",
+            )]));
+        }
+        let mut state = self.state.lock().await;
+        if let Some((name, _)) = state.macro_recordings.get(&self.session) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Already recording macro {name}, stop it with stop_macro_recording first"
+            ))]));
+        }
+        state
+            .macro_recordings
+            .insert(self.session, (args.name.clone(), Vec::new()));
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Recording macro {}",
+            args.name
+        ))]))
+    }
+
+    #[tool(
+        description = "Stop recording the current macro and save it. Macros are kept for the session, and also written to macros/<name>.json in the files directory when the server has one, so later sessions can replay them. Returns the macro's name, steps and where it was saved as JSON."
+    )]
+    async fn stop_macro_recording(
+        &self,
+        Parameters(_args): Parameters<StopMacroRecording>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let recording = self
+            .state
+            .lock()
+            .await
+            .macro_recordings
+            .remove(&self.session);
+        let Some((name, steps)) = recording else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "No macro is being recorded, start one with start_macro_recording",
+            )]));
+        };
+        if steps.is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Macro {name} recorded no tool calls and was discarded"
+            ))]));
+        }
+
+        let saved_to = match self.macro_path(&name) {
+            Some(path) => {
+                let written = match path {
+                    Ok(path) => Self::write_macro(&path, &steps).await.map(|_| path),
+                    Err(err) => Err(err),
+                };
+                match written {
+                    Ok(path) => Some(path.display().to_string()),
+                    Err(err) => {
+                        tracing::warn!("Could not save macro {name}: {err:#}");
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let report = json!({
+            "name": name,
+            "steps": steps.iter().map(step_name).collect::<Vec<_>>(),
+            "saved_to": saved_to,
+        });
+        self.state.lock().await.macros.insert(name, steps);
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Replay a macro recorded with start_macro_recording, in this or an earlier session, running its tool calls in order with their usual timeouts. Returns JSON with each step's tool, whether it succeeded and the start of its response, whether all steps ran and how many failed."
+    )]
+    async fn replay_macro(
+        &self,
+        Parameters(args): Parameters<ReplayMacro>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let steps = match self.load_macro(&args.name).await {
+            Ok(steps) => steps,
+            Err(err) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{err:#}"
+                ))]))
+            }
+        };
+        // The macro may come from a file or a session with other settings, so every step is checked before any runs
+        for (index, step) in steps.iter().enumerate() {
+            let tool = step_tool_name(step);
+            if !self.tool_router.has_route(&tool) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Step {} of macro {} calls {tool}, which is disabled on this server",
+                    index + 1,
+                    args.name
+                ))]));
+            }
+        }
+        let stop_on_error = args.stop_on_error.unwrap_or(true);
+        let total = steps.len();
+        let mut results = Vec::new();
+        for (index, step) in steps.into_iter().enumerate() {
+            let tool = step_name(&step);
+            let (success, response) = match self.send_to_studio(step).await? {
+                Ok(response) => (true, response),
+                Err(err) => (false, err.to_string()),
+            };
+            results.push(json!({
+                "step": index + 1,
+                "tool": tool,
+                "success": success,
+                "response": response.chars().take(MACRO_RESPONSE_PREVIEW).collect::<String>(),
+            }));
+            if !success && stop_on_error {
+                break;
+            }
+        }
+        let report = json!({
+            "name": args.name,
+            "completed": results.len() == total,
+            "failed": results.iter().filter(|result| result["success"] == false).count(),
+            "steps": results,
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "List the macros recorded in this session and the ones saved in the files directory, and the macro being recorded, as JSON.",
+        annotations(read_only_hint = true)
+    )]
+    async fn list_macros(
+        &self,
+        Parameters(_args): Parameters<ListMacros>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let state = self.state.lock().await;
+        let mut names: BTreeSet<String> = state.macros.keys().cloned().collect();
+        let saved_dir = self
+            .config
+            .files_dir
+            .as_ref()
+            .and_then(|_| self.config.files_path(MACROS_DIR).ok());
+        if let Some(dir) = saved_dir {
+            for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    if let Some(stem) = path.file_stem() {
+                        names.insert(stem.to_string_lossy().into_owned());
+                    }
+                }
+            }
+        }
+        let report = json!({
+            "macros": names,
+            "recording": state.macro_recordings.get(&self.session).map(|(name, steps)| {
+                json!({ "name": name, "steps": steps.iter().map(step_name).collect::<Vec<_>>() })
+            }),
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            report.to_string(),
+        )]))
+    }
+
+    #[tool(
+        description = "Run several commands in Roblox Studio in one round trip, in order and as a single undo step. Returns a JSON array with each command's index, success and printed output like run_code's; commands skipped after a failure have skipped set to true."
+    )]
+    async fn run_batch(
+        &self,
+        Parameters(args): Parameters<RunBatch>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::RunBatch(args))
+            .await
+    }
 
-#[tool_handler]
-impl ServerHandler for RBXStudioServer {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            protocol_version: ProtocolVersion::LATEST,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            server_info: Implementation {
-                name: "Roblox_Studio".to_string(),
-                version: env!("CARGO_PKG_VERSION").to_string(),
-                title: Some("Roblox Studio MCP Server".to_string()),
-                icons: None,
-                website_url: None,
-            },
-            instructions: Some(
-                "You must aware of current studio mode before using any tools, infer the mode from conversation context or get_studio_mode.
-User run_code to query data from Roblox Studio place or to change it
-After calling run_script_in_play_mode, the datamodel status will be reset to stop mode.
-Prefer using start_stop_play tool instead run_script_in_play_mode, Only used run_script_in_play_mode to run one time unit test code on server datamodel.
-"
-                    .to_string(),
-            ),
-        }
+    #[tool(
+        description = "Check a GUI subtree against several device viewport sizes for multi-device preview. Returns JSON with per-device findings: elements that end up off screen (with their overflow per side) and, on touch devices, buttons and text boxes smaller than 44 pixels. Positions and sizes are in pixels and UDim2 values use the same encoding as get_properties. With fix and confirm, offending offsets are converted to scale."
+    )]
+    async fn check_ui_devices(
+        &self,
+        Parameters(args): Parameters<CheckUiDevices>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::CheckUiDevices(args))
+            .await
     }
-}
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct RunCode {
-    #[schemars(description = "Code to run")]
-    command: String,
-}
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct InsertModel {
-    #[schemars(description = "Query to search for the model")]
-    query: String,
-}
+    #[tool(
+        description = "Export a macro recorded with start_macro_recording as a standalone Luau script that reproduces its changes, e.g. to save in the project. Returns the source. Code from run_code, run_code_json and run_batch is kept as is, set_property, create_instance and delete_instance become plain property assignments and Instance calls. Steps that only read the place, like screenshots, and steps with no Luau equivalent are omitted with a comment saying why.",
+        annotations(read_only_hint = true)
+    )]
+    async fn export_macro_script(
+        &self,
+        Parameters(args): Parameters<ExportMacroScript>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let recording = self
+            .state
+            .lock()
+            .await
+            .macro_recordings
+            .get(&self.session)
+            .filter(|(name, _)| *name == args.name)
+            .cloned();
+        let steps = match recording {
+            Some((_, steps)) => steps,
+            None => match self.load_macro(&args.name).await {
+                Ok(steps) => steps,
+                Err(err) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "{err:#}"
+                    ))]))
+                }
+            },
+        };
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct GetConsoleOutput {}
+        let router = Self::tool_router();
+        let mut script = ScriptBuilder::new(&format!(
+            "Reproduces the changes recorded in macro {}",
+            args.name
+        ));
+        for (index, step) in steps.iter().enumerate() {
+            let number = index + 1;
+            let tool = luau_export::tool_name(&step_name(step));
+            match step {
+                ToolArgumentValues::RunCode(args) => {
+                    script.code(number, &tool, &args.command, args.args.as_ref())
+                }
+                ToolArgumentValues::RunCodeJson(args) => {
+                    script.code(number, &tool, &args.command, args.args.as_ref())
+                }
+                ToolArgumentValues::RunBatch(batch) => {
+                    for command in &batch.commands {
+                        script.code(number, &tool, &command.command, command.args.as_ref());
+                    }
+                }
+                ToolArgumentValues::SetProperty(args) => match script.property_value(&args.value) {
+                    Some(value) => {
+                        let statement = format!(
+                            "{}.{} = {value}",
+                            script.instance(&args.path),
+                            args.property
+                        );
+                        script.statements(number, &tool, &[statement]);
+                    }
+                    None => script.omit(
+                        number,
+                        &tool,
+                        "its value is untagged and depends on the property's type",
+                    ),
+                },
+                ToolArgumentValues::CreateInstance(args) => {
+                    let mut statements = vec![format!(
+                        "local instance = Instance.new({:?})",
+                        args.class_name
+                    )];
+                    let mut properties: Vec<_> = args.properties.iter().flatten().collect();
+                    properties.sort_by_key(|(name, _)| name.as_str());
+                    let mut untagged = Vec::new();
+                    for (name, value) in properties {
+                        match script.property_value(value) {
+                            Some(value) => statements.push(format!("instance.{name} = {value}")),
+                            None => untagged.push(name.as_str()),
+                        }
+                    }
+                    if !untagged.is_empty() {
+                        statements.push(format!(
+                            "-- Untagged values depend on the property's type, set by hand: {}",
+                            untagged.join(", ")
+                        ));
+                    }
+                    statements.push(format!(
+                        "instance.Parent = {}",
+                        script.instance(&args.parent_path)
+                    ));
+                    script.statements(number, &tool, &statements);
+                }
+                ToolArgumentValues::DeleteInstance(args) => {
+                    let statements: Vec<_> = args
+                        .paths
+                        .iter()
+                        .map(|path| format!("{}:Destroy()", script.instance(path)))
+                        .collect();
+                    script.statements(number, &tool, &statements);
+                }
+                ToolArgumentValues::CaptureSelectionThumbnail(_) => {
+                    script.omit(number, &tool, "it only captures the viewport");
+                }
+                _ => {
+                    let read_only = router
+                        .list_all()
+                        .into_iter()
+                        .find(|listed| listed.name == tool)
+                        .and_then(|tool| tool.annotations?.read_only_hint)
+                        .unwrap_or(false);
+                    let reason = if read_only {
+                        "it only reads the place"
+                    } else {
+                        "it has no standalone Luau equivalent"
+                    };
+                    script.omit(number, &tool, reason);
+                }
+            }
+        }
+        Ok(CallToolResult::success(vec![Content::text(
+            script.finish(steps.len()),
+        )]))
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct GetStudioMode {}
+    #[tool(
+        description = "Get the place's audio environment from SoundService as JSON: ambient_reverb, distance_factor and rolloff_scale.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_sound_settings(
+        &self,
+        Parameters(args): Parameters<GetSoundSettings>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetSoundSettings(args))
+            .await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct StartStopPlay {
-    #[schemars(
-        description = "Mode to start or stop, must be start_play, stop, or run_server. Don't use run_server unless you are sure no client/player is needed."
+    #[tool(
+        description = "Set the place's audio environment on SoundService (AmbientReverb, DistanceFactor, RolloffScale) as a single undo step. Values are validated before anything changes. Returns the resolved settings as JSON."
     )]
-    mode: String,
-}
+    async fn set_sound_settings(
+        &self,
+        Parameters(args): Parameters<SetSoundSettings>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetSoundSettings(args))
+            .await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct RunScriptInPlayMode {
-    #[schemars(description = "Code to run")]
-    code: String,
-    #[schemars(description = "Timeout in seconds, defaults to 100 seconds")]
-    timeout: Option<u32>,
-    #[schemars(description = "Mode to run in, must be start_play or run_server")]
-    mode: String,
-}
+    #[tool(
+        description = "Find scripts that likely never run, as candidates for cleanup: ModuleScripts no script requires, disabled Scripts and LocalScripts, and scripts sitting where they can't run (e.g. a LocalScript in ServerScriptService or any script in ServerStorage). Best-effort: requires are resolved statically like get_require_graph, and modules named in requires that can't be resolved are left out. Returns JSON with each candidate's path, class_name, kind (never_required, disabled or never_runs) and reason, plus how many requires couldn't be resolved.",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_unused_scripts(
+        &self,
+        Parameters(args): Parameters<FindUnusedScripts>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindUnusedScripts(args))
+            .await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-enum ToolArgumentValues {
-    RunCode(RunCode),
-    InsertModel(InsertModel),
-    GetConsoleOutput(GetConsoleOutput),
-    StartStopPlay(StartStopPlay),
-    RunScriptInPlayMode(RunScriptInPlayMode),
-    GetStudioMode(GetStudioMode),
-}
-#[tool_router]
-impl RBXStudioServer {
-    pub fn new(state: PackedState) -> Self {
-        Self {
-            state,
-            tool_router: Self::tool_router(),
-        }
+    #[tool(
+        description = "Get the network owner of parts on the server of the running playtest, to debug physics authority. Requires a playtest started with start_stop_play. Returns JSON with each assembly's path, assembly_root, anchored, owner (a player name or \"server\"), auto (whether the engine assigns ownership automatically) and can_set with the reason when ownership can't be changed.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_network_ownership(
+        &self,
+        Parameters(args): Parameters<GetNetworkOwnership>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetNetworkOwnership(args))
+            .await
     }
 
     #[tool(
-        description = "Runs a command in Roblox Studio and returns the printed output. Can be used to both make changes and retrieve information"
+        description = "Set the network owner of a part's assembly on the server of the running playtest with SetNetworkOwner, or return it to automatic assignment. Requires a playtest started with start_stop_play and confirm set to true. Anchored assemblies always belong to the server. Returns the resulting ownership as JSON, like get_network_ownership."
     )]
-    async fn run_code(
+    async fn set_network_ownership(
         &self,
-        Parameters(args): Parameters<RunCode>,
+        Parameters(args): Parameters<SetNetworkOwnership>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::RunCode(args))
+        self.generic_tool_run(ToolArgumentValues::SetNetworkOwnership(args))
             .await
     }
 
     #[tool(
-        description = "Inserts a model from the Roblox marketplace into the workspace. Returns the inserted model name."
+        description = "Get an overview of the place in one call, a good first step in an unfamiliar place. Returns JSON with the place's name and ids, each service with its instance and script counts, the most common classes, script counts by class with disabled scripts and total lines, and the first findings of find_structural_issues, find_run_context_issues and find_deprecated_apis. Each section is capped and has a total; call the individual tools for the full findings. A section that fails holds an error instead.",
+        annotations(read_only_hint = true)
     )]
-    async fn insert_model(
+    async fn get_place_summary(
         &self,
-        Parameters(args): Parameters<InsertModel>,
+        Parameters(args): Parameters<GetPlaceSummary>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::InsertModel(args))
+        self.generic_tool_run(ToolArgumentValues::GetPlaceSummary(args))
             .await
     }
 
-    #[tool(description = "Get the console output from Roblox Studio.")]
-    async fn get_console_output(
+    #[tool(
+        description = "Get the place's chat setup from TextChatService and its ChatWindow, ChatInputBar and BubbleChat configurations, plus the TextChannels in TextChatService. Returns JSON with settings (chat_version, create_default_text_channels, create_default_commands, chat_window_enabled, chat_window_text_size, chat_input_bar_enabled, bubble_chat_enabled, bubble_max_distance, bubble_duration) and channels. The default channels only exist while the game runs.",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_text_chat_settings(
         &self,
-        Parameters(args): Parameters<GetConsoleOutput>,
+        Parameters(args): Parameters<GetTextChatSettings>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GetConsoleOutput(args))
+        self.generic_tool_run(ToolArgumentValues::GetTextChatSettings(args))
             .await
     }
 
     #[tool(
-        description = "Start or stop play mode or run the server, Don't enter run_server mode unless you are sure no client/player is needed."
+        description = "Change TextChatService settings and add TextChannels as a single undo step. Values are validated before anything changes. Switching chat_version between TextChatService and LegacyChatService changes which chat system runs and requires confirm set to true. Returns the resolved settings and channels as JSON, like get_text_chat_settings."
     )]
-    async fn start_stop_play(
+    async fn set_text_chat_settings(
         &self,
-        Parameters(args): Parameters<StartStopPlay>,
+        Parameters(args): Parameters<SetTextChatSettings>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::StartStopPlay(args))
+        self.generic_tool_run(ToolArgumentValues::SetTextChatSettings(args))
             .await
     }
 
     #[tool(
-        description = "Run a script in play mode and automatically stop play after script finishes or timeout. Returns the output of the script.
-        Result format: { success: boolean, value: string, error: string, logs: { level: string, message: string, ts: number }[], errors: { level: string, message: string, ts: number }[], duration: number, isTimeout: boolean }.
-        - Prefer using start_stop_play tool instead run_script_in_play_mode, Only used run_script_in_play_mode to run one time unit test code on server datamodel.
-        - After calling run_script_in_play_mode, the datamodel status will be reset to stop mode.
-        - If It returns `StudioTestService: Previous call to start play session has not been completed`, call start_stop_play tool to stop play mode first then try it again."
+        description = "Time a Luau snippet in Roblox Studio to compare implementations. Runs setup once, then code for warmup untimed and iterations timed runs in a sandboxed global environment. Stops early after 10 seconds or when cancelled. Returns JSON with iterations run, requested_iterations, warmup, total_seconds, stopped_early and stats in microseconds: min_us, max_us, mean_us, median_us, p95_us and stddev_us. The snippet can change the place like run_code."
     )]
-    async fn run_script_in_play_mode(
+    async fn benchmark_code(
         &self,
-        Parameters(args): Parameters<RunScriptInPlayMode>,
+        Parameters(args): Parameters<BenchmarkCode>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::RunScriptInPlayMode(args))
+        self.generic_tool_run(ToolArgumentValues::BenchmarkCode(args))
             .await
     }
 
     #[tool(
-        description = "Get the current studio mode. Returns the studio mode. The result will be one of start_play, run_server, or stop."
+        description = "Get the open place's version metadata to correlate edits with saves: published, place_id, game_id, place_version (the version the place was loaded at), creator_id and creator_type, created and updated (first and last save or publish to Roblox) for published places, last_tracked_change (when the plugin last saw the place change) and checked_at. Also lists what only the Creator Dashboard offers, with a dashboard_url to the version history.",
+        annotations(read_only_hint = true)
     )]
-    async fn get_studio_mode(
+    async fn get_place_version(
         &self,
-        Parameters(args): Parameters<GetStudioMode>,
+        Parameters(args): Parameters<GetPlaceVersion>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GetStudioMode(args))
+        self.generic_tool_run(ToolArgumentValues::GetPlaceVersion(args))
             .await
     }
 
+    #[tool(
+        description = "Find instances with a CSS-like selector instead of writing recursive Luau, e.g. Workspace > Model Part[Anchored=true] or .Enemy > Humanoid[Health=0]. See the selector argument for the supported syntax. Returns JSON with the matching paths in place order, the total count and whether the paths were truncated. Invalid selectors are reported with the column of the error.",
+        annotations(read_only_hint = true)
+    )]
+    async fn query_selector(
+        &self,
+        Parameters(args): Parameters<QuerySelector>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let selectors = match selector::parse(&args.selector) {
+            Ok(selectors) => selectors,
+            Err(err) => {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    err.describe(&args.selector),
+                )]))
+            }
+        };
+        self.generic_tool_run(ToolArgumentValues::MatchSelector(MatchSelector {
+            path: args.path,
+            selectors,
+            max_results: args.max_results,
+        }))
+        .await
+    }
+
     async fn generic_tool_run(
         &self,
         args: ToolArgumentValues,
     ) -> Result<CallToolResult, ErrorData> {
-        let (command, id) = ToolArguments::new(args);
+        // Commands sent on behalf of another tool, like ApplyScripts for import_scripts, aren't recorded
+        let recording = self
+            .state
+            .lock()
+            .await
+            .macro_recordings
+            .contains_key(&self.session)
+            && self.tool_router.has_route(&step_tool_name(&args));
+        let step = recording.then(|| args.clone());
+        match self.send_to_studio(args).await? {
+            Ok(result) => {
+                // Failed calls would fail again on replay, so only successful ones are recorded
+                if let Some(step) = step {
+                    let mut state = self.state.lock().await;
+                    if let Some((_, steps)) = state.macro_recordings.get_mut(&self.session) {
+                        steps.push(step);
+                    }
+                }
+                Ok(tool_result(result))
+            }
+            Err(err) => Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+        }
+    }
+
+    /// Where a macro is saved in the files directory, if one is configured
+    fn macro_path(&self, name: &str) -> Option<color_eyre::Result<PathBuf>> {
+        self.config.files_dir.as_ref()?;
+        Some(self.config.files_path(&format!("{MACROS_DIR}/{name}.json")))
+    }
+
+    /// Saves the steps of a macro as JSON, creating the macros directory when needed
+    async fn write_macro(
+        path: &std::path::Path,
+        steps: &[ToolArgumentValues],
+    ) -> color_eyre::Result<()> {
+        let json = serde_json::to_string_pretty(steps)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+
+    /// Finds a macro recorded in this session or saved by an earlier one
+    async fn load_macro(&self, name: &str) -> color_eyre::Result<Vec<ToolArgumentValues>> {
+        if let Some(steps) = self.state.lock().await.macros.get(name) {
+            return Ok(steps.clone());
+        }
+        let path = self
+            .macro_path(name)
+            .ok_or_else(|| eyre!("No macro named {name} was recorded in this session"))??;
+        let json = std::fs::read_to_string(&path).wrap_err_with(|| {
+            format!("No macro named {name}, could not read {}", path.display())
+        })?;
+        let steps: Vec<ToolArgumentValues> = serde_json::from_str(&json)
+            .wrap_err_with(|| format!("{} is not a valid macro", path.display()))?;
+        // Only calls of tools themselves are recorded, never commands sent on behalf of another tool
+        let tools = Self::tool_router();
+        if let Some((index, step)) = steps
+            .iter()
+            .enumerate()
+            .find(|(_, step)| !tools.has_route(&step_tool_name(step)))
+        {
+            return Err(eyre!(
+                "{} is not a valid macro, step {} is {}, which is not a tool",
+                path.display(),
+                index + 1,
+                step_name(step)
+            ));
+        }
+        Ok(steps)
+    }
+
+    /// URL game code reaches a mock at, on loopback when the server listens on all interfaces
+    fn mock_url(&self, path: &str) -> String {
+        let host = if self.config.host.is_unspecified() {
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        } else {
+            self.config.host
+        };
+        format!(
+            "http://{}/mock/{path}",
+            SocketAddr::new(host, self.config.port)
+        )
+    }
+
+    fn mock_list(&self, mocks: &BTreeMap<String, HttpMock>) -> Value {
+        mocks
+            .iter()
+            .map(|(path, mock)| {
+                json!({
+                    "url": self.mock_url(path),
+                    "status": mock.status.as_u16(),
+                    "content_type": mock.content_type,
+                    "hits": mock.hits,
+                })
+            })
+            .collect()
+    }
+
+    /// Queues a command for the plugin and waits for its raw response
+    async fn send_to_studio(&self, args: ToolArgumentValues) -> Result<Result<String>, ErrorData> {
+        let timeout = command_timeout(self.config.command_timeout, &args);
+        self.send_to_studio_within(args, timeout).await
+    }
+
+    /// Like `send_to_studio` with its own deadline, after which the command is cancelled like a client cancellation
+    async fn send_to_studio_within(
+        &self,
+        args: ToolArgumentValues,
+        timeout: Option<Duration>,
+    ) -> Result<Result<String>, ErrorData> {
+        let (command, id) = ToolArguments::new(args, self.session);
         tracing::debug!("Running command: {:?}", command);
         let (tx, mut rx) = mpsc::unbounded_channel::<Result<String>>();
+        let progress = TOOL_CALL
+            .try_with(|context| Some((context.peer.clone(), context.meta.get_progress_token()?)))
+            .ok()
+            .flatten();
+        let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+        let cancellation = TOOL_CALL.try_with(|context| context.ct.clone()).ok();
         let trigger = {
             let mut state = self.state.lock().await;
+            if let Some(window) = self.config.connection_timeout {
+                if !state.studio_connected(window) {
+                    // A plugin that went away won't answer the commands it was running
+                    state.cancelled.clear();
+                    return Ok(Err(Report::from(eyre!(
+                        "Roblox Studio is not connected, the MCP plugin hasn't polled the server in the last {}s. \
+                         Check that Studio is open and the plugin is enabled",
+                        window.as_secs()
+                    ))));
+                }
+            }
             state.process_queue.push_back(command);
             state.output_map.insert(id, tx);
+            if progress.is_some() {
+                state.progress_map.insert(id, progress_tx);
+            }
             state.trigger.clone()
         };
+        let pending = PendingCommand::new(&self.state, id);
         trigger
             .send(())
             .map_err(|e| ErrorData::internal_error(format!("Unable to trigger send {e}"), None))?;
-        let result = rx
-            .recv()
-            .await
-            .ok_or(ErrorData::internal_error("Couldn't receive response", None))?;
+        let progress = progress.map(|(peer, token)| (peer, token, progress_rx));
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let cancelled = async {
+            match &cancellation {
+                Some(cancellation) => cancellation.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        let received = tokio::select! {
+            received = wait_for_response(&mut rx, progress, deadline) => received,
+            _ = cancelled => {
+                pending.cancel().await;
+                return Ok(Err(Report::from(eyre!("The command was cancelled"))));
+            }
+        };
+        self.state.lock().await.progress_map.remove(&id);
+        let Some(result) = received else {
+            pending.cancel().await;
+            return Ok(Err(Report::from(eyre!(
+                "The command did not complete within {}s, check that Studio is open and the MCP plugin is connected",
+                timeout.unwrap_or_default().as_secs()
+            ))));
+        };
+        pending.finish();
+        let result = result.ok_or(ErrorData::internal_error("Couldn't receive response", None))?;
         {
             let mut state = self.state.lock().await;
             state.output_map.remove_entry(&id);
         }
         tracing::debug!("Sending to MCP: {result:?}");
-        match result {
-            Ok(result) => Ok(CallToolResult::success(vec![Content::text(result)])),
-            Err(err) => Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+        Ok(result)
+    }
+}
+
+/// Wraps a plugin response as a tool result, keeping the text for clients that only read content. Responses that
+/// are JSON objects are also sent as structured content, and `_meta.json` says whether the response parsed as JSON.
+fn tool_result(response: String) -> CallToolResult {
+    let parsed = serde_json::from_str::<Value>(&response).ok();
+    let mut meta = Meta::new();
+    meta.0
+        .insert("json".to_string(), Value::Bool(parsed.is_some()));
+    let mut result = CallToolResult::success(vec![Content::text(response)]);
+    result.structured_content = parsed.filter(Value::is_object);
+    result.meta = Some(meta);
+    result
+}
+
+/// Waits for a command's response until the deadline, `None` once it passes. When the client asked for progress, it
+/// gets a heartbeat every few seconds until the plugin reports progress of its own, which is relayed from then on.
+async fn wait_for_response(
+    rx: &mut mpsc::UnboundedReceiver<Result<String>>,
+    progress: Option<(
+        Peer<RoleServer>,
+        ProgressToken,
+        mpsc::UnboundedReceiver<PluginProgress>,
+    )>,
+    deadline: Option<Instant>,
+) -> Option<Option<Result<String>>> {
+    let expired = async {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(expired);
+    let Some((peer, progress_token, mut updates)) = progress else {
+        return tokio::select! {
+            result = rx.recv() => Some(result),
+            _ = &mut expired => None,
+        };
+    };
+
+    let started = Instant::now();
+    let mut heartbeat = tokio::time::interval_at(started + PROGRESS_INTERVAL, PROGRESS_INTERVAL);
+    let mut plugin_reported = false;
+    let mut last = f64::NEG_INFINITY;
+    loop {
+        let (progress, total, message) = tokio::select! {
+            result = rx.recv() => return Some(result),
+            _ = &mut expired => return None,
+            _ = heartbeat.tick(), if !plugin_reported => {
+                let elapsed = started.elapsed().as_secs();
+                (elapsed as f64, None, format!("Waiting for Studio, {elapsed}s elapsed"))
+            }
+            Some(update) = updates.recv() => {
+                // Reports are posted without waiting, so they can arrive out of order
+                if plugin_reported && update.progress <= last {
+                    continue;
+                }
+                plugin_reported = true;
+                (update.progress, update.total, update.message.unwrap_or_default())
+            }
+        };
+        last = progress;
+        let notified = peer
+            .notify_progress(ProgressNotificationParam {
+                progress_token: progress_token.clone(),
+                progress,
+                total,
+                message: (!message.is_empty()).then_some(message),
+            })
+            .await;
+        if let Err(e) = notified {
+            tracing::warn!("Unable to send progress notification: {e}");
         }
     }
 }
 
+/// Encodes a CFrame given as a position or 12 components the way the plugin's ValueCodec does
+fn encode_cframe(value: &Value) -> Option<Value> {
+    let components = match value {
+        Value::Object(tagged) if tagged.get("type")? == "CFrame" => tagged.get("value")?,
+        value => value,
+    };
+    let numbers = components
+        .as_array()?
+        .iter()
+        .map(Value::as_f64)
+        .collect::<Option<Vec<_>>>()?;
+    let components = match numbers.len() {
+        3 => [&numbers[..], &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]].concat(),
+        12 => numbers,
+        _ => return None,
+    };
+    Some(json!({ "type": "CFrame", "value": components }))
+}
+
+fn bookmark_list(bookmarks: &BTreeMap<String, Value>) -> Value {
+    bookmarks
+        .iter()
+        .map(|(name, view)| json!({ "name": name, "cframe": view["cframe"], "focus": view["focus"] }))
+        .collect()
+}
+
+/// Resident and virtual memory of this process in bytes, if the platform reports them
+fn process_memory() -> Option<(u64, u64)> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        false,
+        sysinfo::ProcessRefreshKind::nothing().with_memory(),
+    );
+    let process = system.process(pid)?;
+    Some((process.memory(), process.virtual_memory()))
+}
+
+/// Counts a poll as open until its handler finishes or is dropped
+struct OpenPoll(Arc<AtomicUsize>);
+
+impl OpenPoll {
+    fn new(open_polls: &Arc<AtomicUsize>) -> Self {
+        open_polls.fetch_add(1, Ordering::Relaxed);
+        Self(Arc::clone(open_polls))
+    }
+}
+
+impl Drop for OpenPoll {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 pub async fn request_handler(State(state): State<PackedState>) -> Result<impl IntoResponse> {
-    let timeout = tokio::time::timeout(LONG_POLL_DURATION, async {
+    let (poll_duration, _open_poll) = {
+        let mut state = state.lock().await;
+        state.last_poll = Some(Instant::now());
+        (state.poll_duration, OpenPoll::new(&state.open_polls))
+    };
+    let timeout = tokio::time::timeout(poll_duration, async {
         let mut waiter = { state.lock().await.waiter.clone() };
         loop {
             {
                 let mut state = state.lock().await;
-                if let Some(task) = state.process_queue.pop_front() {
-                    return Ok::<ToolArguments, Error>(task);
+                while let Some(task) = state.process_queue.pop_front() {
+                    // Skip commands nobody is waiting for anymore
+                    if let Some(id) = task.id.filter(|id| state.output_map.contains_key(id)) {
+                        state.in_flight.insert(id, task.session);
+                        return Ok::<ToolArguments, Error>(task);
+                    }
                 }
             }
             waiter.changed().await?
         }
     })
     .await;
+    state.lock().await.last_poll = Some(Instant::now());
     match timeout {
         Ok(result) => Ok(Json(result?).into_response()),
         _ => Ok((StatusCode::LOCKED, String::new()).into_response()),
@@ -279,10 +4012,16 @@ pub async fn response_handler(
 ) -> Result<impl IntoResponse> {
     tracing::debug!("Received reply from studio {payload:?}");
     let mut state = state.lock().await;
+    state.in_flight.remove(&payload.id);
+    if state.cancelled.remove(&payload.id).is_some() {
+        return Ok(());
+    }
     let tx = state
         .output_map
         .remove(&payload.id)
         .ok_or_eyre("Unknown ID")?;
+    state.progress_map.remove(&payload.id);
+    state.stats.record(payload.success);
     let result: Result<String, Report> = if payload.success {
         Ok(payload.response)
     } else {
@@ -291,26 +4030,83 @@ pub async fn response_handler(
     Ok(tx.send(result)?)
 }
 
+/// Tells the plugin whether the client cancelled the command it is running
+pub async fn cancel_handler(State(state): State<PackedState>, Path(id): Path<Uuid>) -> Json<bool> {
+    Json(state.lock().await.cancelled.contains_key(&id))
+}
+
+/// Relays progress the plugin posts for a running command. Commands proxied from another server instance have no
+/// waiting tool call here, so their progress is dropped.
+pub async fn progress_handler(
+    State(state): State<PackedState>,
+    Json(update): Json<PluginProgress>,
+) -> impl IntoResponse {
+    let state = state.lock().await;
+    match state.progress_map.get(&update.id) {
+        Some(tx) if tx.send(update).is_ok() => StatusCode::NO_CONTENT,
+        _ => StatusCode::NOT_FOUND,
+    }
+}
+
+pub async fn mock_handler(
+    State(state): State<PackedState>,
+    Path(path): Path<String>,
+) -> impl IntoResponse {
+    let mut state = state.lock().await;
+    let Some(mock) = state.http_mocks.get_mut(&path) else {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("No HTTP mock is registered for {path}"),
+        )
+            .into_response();
+    };
+    mock.hits += 1;
+    (
+        mock.status,
+        [(header::CONTENT_TYPE, mock.content_type.clone())],
+        mock.body.clone(),
+    )
+        .into_response()
+}
+
 pub async fn proxy_handler(
     State(state): State<PackedState>,
-    Json(command): Json<ToolArguments>,
+    Json(mut command): Json<ToolArguments>,
 ) -> Result<impl IntoResponse> {
     let id = command.id.ok_or_eyre("Got proxy command with no id")?;
+    command.queued_at = Some(Instant::now());
     tracing::debug!("Received request to proxy {command:?}");
     let (tx, mut rx) = mpsc::unbounded_channel();
-    {
+    let timeout = {
         let mut state = state.lock().await;
+        let timeout = command_timeout(state.command_timeout, &command.args);
         state.process_queue.push_back(command);
         state.output_map.insert(id, tx);
-    }
-    let result = rx.recv().await.ok_or_eyre("Couldn't receive response")?;
-    {
-        let mut state = state.lock().await;
-        state.output_map.remove_entry(&id);
-    }
-    let (success, response) = match result {
-        Ok(s) => (true, s),
-        Err(e) => (false, e.to_string()),
+        // Wakes a poll that is being held open
+        state.trigger.send(()).ok();
+        timeout
+    };
+    let pending = PendingCommand::new(&state, id);
+    let received = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, rx.recv()).await.ok(),
+        None => Some(rx.recv().await),
+    };
+    let (success, response) = match received {
+        Some(result) => {
+            pending.finish();
+            match result.ok_or_eyre("Couldn't receive response")? {
+                Ok(s) => (true, s),
+                Err(e) => (false, e.to_string()),
+            }
+        }
+        // Dropping `pending` cancels the command
+        None => (
+            false,
+            format!(
+                "The command did not complete within {}s, check that Studio is open and the MCP plugin is connected",
+                timeout.unwrap_or_default().as_secs()
+            ),
+        ),
     };
     tracing::debug!("Sending back to dud: success={success}, response={response:?}");
     Ok(Json(RunCommandResponse {
@@ -320,38 +4116,59 @@ pub async fn proxy_handler(
     }))
 }
 
-pub async fn dud_proxy_loop(state: PackedState, exit: Receiver<()>) {
+/// Forwards commands to the server instance owning the plugin port. Each command is forwarded on its own, so one
+/// the plugin never answers doesn't hold up the rest.
+pub async fn dud_proxy_loop(
+    state: PackedState,
+    exit: Receiver<()>,
+    host: IpAddr,
+    port: u16,
+    timeout: Option<Duration>,
+) {
     let client = reqwest::Client::new();
+    // The instance owning the port listens on all interfaces, loopback included
+    let host = if host.is_unspecified() {
+        IpAddr::V4(Ipv4Addr::LOCALHOST)
+    } else {
+        host
+    };
+    let proxy_url = format!("http://{}/proxy", SocketAddr::new(host, port));
 
-    let mut waiter = { state.lock().await.waiter.clone() };
+    let mut waiter = {
+        let mut state = state.lock().await;
+        // The plugin polls the other instance, which fails its own commands when Studio is gone
+        state.proxied = true;
+        state.waiter.clone()
+    };
     while exit.is_empty() {
         let entry = { state.lock().await.process_queue.pop_front() };
-        if let Some(entry) = entry {
-            let res = client
-                .post(format!("http://127.0.0.1:{STUDIO_PLUGIN_PORT}/proxy"))
-                .json(&entry)
-                .send()
-                .await;
-            if let Ok(res) = res {
-                let tx = {
-                    state
-                        .lock()
-                        .await
-                        .output_map
-                        .remove(&entry.id.unwrap())
-                        .unwrap()
-                };
-                let res = res
-                    .json::<RunCommandResponse>()
-                    .await
-                    .map(|r| r.response)
-                    .map_err(Into::into);
-                tx.send(res).unwrap();
-            } else {
-                tracing::error!("Failed to proxy: {res:?}");
-            };
-        } else {
+        let Some(entry) = entry else {
             waiter.changed().await.unwrap();
+            continue;
+        };
+        let mut request = client.post(&proxy_url).json(&entry);
+        if let Some(timeout) = command_timeout(timeout, &entry.args) {
+            request = request.timeout(timeout + PROXY_GRACE);
         }
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            let res = match request.send().await {
+                Ok(res) => res.json::<RunCommandResponse>().await,
+                Err(err) => Err(err),
+            };
+            // Gone when the client cancelled the command or gave up on it while it was being proxied
+            let Some(tx) = state.lock().await.output_map.remove(&entry.id.unwrap()) else {
+                return;
+            };
+            let res = match res {
+                Ok(res) if res.success => Ok(res.response),
+                Ok(res) => Err(eyre!(res.response).into()),
+                Err(err) => {
+                    tracing::error!("Failed to proxy: {err:?}");
+                    Err(Report::from(err))
+                }
+            };
+            tx.send(res).ok();
+        });
     }
 }
@@ -0,0 +1,44 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use color_eyre::eyre::{eyre, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Viewport capture as sent by the plugin, pixel rows are base64 encoded 8-bit RGB
+#[derive(Deserialize, Debug)]
+pub struct Capture {
+    width: u32,
+    height: u32,
+    format: String,
+    data: String,
+    /// Resolution, anti-aliasing and render quality the plugin used
+    pub settings: Value,
+}
+
+impl Capture {
+    /// Encodes the capture as a PNG and returns it base64 encoded, ready for an image `Content`
+    pub fn to_png_base64(&self) -> Result<String> {
+        if self.format != "rgb8" {
+            return Err(eyre!("Unsupported capture format {}", self.format));
+        }
+        let pixels = STANDARD.decode(&self.data)?;
+        let expected = self.width as usize * self.height as usize * 3;
+        if pixels.len() != expected {
+            return Err(eyre!(
+                "Capture has {} bytes, expected {expected} for {}x{}",
+                pixels.len(),
+                self.width,
+                self.height
+            ));
+        }
+
+        let mut png_bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png_bytes, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&pixels)?;
+        }
+        Ok(STANDARD.encode(png_bytes))
+    }
+}
@@ -0,0 +1,286 @@
+use similar::TextDiff;
+use std::time::Duration;
+
+/// Sources larger than this are rejected rather than diffed
+pub const MAX_SOURCE_BYTES: usize = 1024 * 1024;
+const MAX_DIFF_CHARS: usize = 100_000;
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Returns a unified diff turning `old` into `new`, truncated if it is very long
+pub fn unified_diff(old_name: &str, old: &str, new_name: &str, new: &str) -> String {
+    let diff = TextDiff::configure()
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(old, new);
+    let patch = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(old_name, new_name)
+        .to_string();
+    if patch.chars().count() <= MAX_DIFF_CHARS {
+        return patch;
+    }
+    let kept: String = patch.chars().take(MAX_DIFF_CHARS).collect();
+    format!("{kept}\n... diff truncated after {MAX_DIFF_CHARS} characters")
+}
+
+#[derive(Debug)]
+enum HunkLine<'a> {
+    Context(&'a str),
+    Remove(&'a str),
+    Add(&'a str),
+}
+
+#[derive(Debug)]
+struct Hunk<'a> {
+    header: &'a str,
+    old_start: usize,
+    lines: Vec<HunkLine<'a>>,
+    text: String,
+    /// Set by `\ No newline at end of file` after the old or new version's last line
+    old_missing_newline: bool,
+    new_missing_newline: bool,
+}
+
+impl Hunk<'_> {
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Remove(s) => Some(*s),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// Records a `\ No newline at end of file` marker, which is about the line before it
+    fn mark_missing_newline(&mut self) {
+        match self.lines.last() {
+            Some(HunkLine::Context(_)) => {
+                self.old_missing_newline = true;
+                self.new_missing_newline = true;
+            }
+            Some(HunkLine::Remove(_)) => self.old_missing_newline = true,
+            Some(HunkLine::Add(_)) => self.new_missing_newline = true,
+            None => {}
+        }
+    }
+
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Add(s) => Some(*s),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Parses `-start,count +start,count` out of a `@@ ... @@` hunk header
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize)> {
+    let ranges = header.strip_prefix("@@ ")?.split(" @@").next()?;
+    let mut parts = ranges.split(' ');
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        let mut it = range.splitn(2, ',');
+        let start = it.next()?.parse().ok()?;
+        let count = it.next().map_or(Some(1), |c| c.parse().ok())?;
+        Some((start, count))
+    };
+    let (old_start, old_count) = parse_range(parts.next()?.strip_prefix('-')?)?;
+    let (_, new_count) = parse_range(parts.next()?.strip_prefix('+')?)?;
+    Some((old_start, old_count, new_count))
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<Hunk<'_>>, String> {
+    let mut hunks = Vec::new();
+    let mut lines = patch.lines().peekable();
+    while let Some(line) = lines.next() {
+        if !line.starts_with("@@") {
+            // File headers and any other preamble
+            continue;
+        }
+        let (old_start, mut old_left, mut new_left) =
+            parse_hunk_header(line).ok_or_else(|| format!("Invalid hunk header: {line}"))?;
+        let mut hunk = Hunk {
+            header: line,
+            old_start,
+            lines: Vec::new(),
+            text: line.to_string(),
+            old_missing_newline: false,
+            new_missing_newline: false,
+        };
+        while old_left > 0 || new_left > 0 {
+            let Some(line) = lines.next() else {
+                return Err(format!("Hunk {} ends early", hunk.header));
+            };
+            hunk.text.push('\n');
+            hunk.text.push_str(line);
+            if line.starts_with('\\') {
+                hunk.mark_missing_newline();
+                continue;
+            }
+            let (kind, content) = line.split_at(line.len().min(1));
+            match kind {
+                "+" => {
+                    hunk.lines.push(HunkLine::Add(content));
+                    new_left = new_left.saturating_sub(1);
+                }
+                "-" => {
+                    hunk.lines.push(HunkLine::Remove(content));
+                    old_left = old_left.saturating_sub(1);
+                }
+                // Some tools strip the leading space of empty context lines
+                " " | "" => {
+                    hunk.lines.push(HunkLine::Context(content));
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+                _ => return Err(format!("Invalid line in hunk {}: {line}", hunk.header)),
+            }
+        }
+        while let Some(marker) = lines.next_if(|l| l.starts_with('\\')) {
+            hunk.text.push('\n');
+            hunk.text.push_str(marker);
+            hunk.mark_missing_newline();
+        }
+        hunks.push(hunk);
+    }
+    if hunks.is_empty() {
+        return Err("Patch contains no hunks".to_string());
+    }
+    Ok(hunks)
+}
+
+fn find_block(lines: &[&str], block: &[&str], expected: usize, min: usize) -> Option<usize> {
+    let last = lines.len().checked_sub(block.len())?;
+    if last < min {
+        return None;
+    }
+    let expected = expected.clamp(min, last);
+    let matches = |pos: usize| lines[pos..pos + block.len()] == *block;
+    // Search outwards from the expected position so the closest match wins
+    (0..=last - min).find_map(|distance| {
+        [
+            expected.checked_sub(distance),
+            expected.checked_add(distance),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|pos| (min..=last).contains(pos))
+        .find(|pos| matches(*pos))
+    })
+}
+
+/// Outcome of applying a patch, `failed_hunks` holds the text of hunks whose context wasn't found
+pub struct PatchResult {
+    pub source: String,
+    pub failed_hunks: Vec<String>,
+}
+
+/// Applies a unified diff to `source`. Nothing is applied unless every hunk applies. The source keeps its line endings,
+/// and whether it ends with a newline unless a hunk at its end says otherwise.
+pub fn apply_patch(source: &str, patch: &str) -> Result<PatchResult, String> {
+    let hunks = parse_patch(patch)?;
+    let line_ending = if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut final_newline = source.ends_with('\n') || source.is_empty();
+    let mut lines: Vec<&str> = source.lines().collect();
+    let mut failed_hunks = Vec::new();
+    let mut delta: isize = 0;
+    let mut min = 0;
+    for hunk in &hunks {
+        let old = hunk.old_lines();
+        let new = hunk.new_lines();
+        // Line numbers are 1-based, and 0 for hunks that insert into an empty file
+        let expected = (hunk.old_start.saturating_sub(1) as isize + delta).max(0) as usize;
+        match find_block(&lines, &old, expected, min) {
+            Some(pos) => {
+                if pos + old.len() == lines.len() {
+                    if hunk.new_missing_newline {
+                        final_newline = false;
+                    } else if hunk.old_missing_newline {
+                        final_newline = true;
+                    }
+                }
+                lines.splice(pos..pos + old.len(), new.iter().copied());
+                delta += new.len() as isize - old.len() as isize;
+                min = pos + new.len();
+            }
+            None => failed_hunks.push(hunk.text.clone()),
+        }
+    }
+
+    let mut patched = lines.join(line_ending);
+    if final_newline && !lines.is_empty() {
+        patched.push_str(line_ending);
+    }
+    Ok(PatchResult {
+        source: if failed_hunks.is_empty() {
+            patched
+        } else {
+            source.to_string()
+        },
+        failed_hunks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(source: &str, patch: &str) -> PatchResult {
+        apply_patch(source, patch).unwrap()
+    }
+
+    #[test]
+    fn context_mismatch_leaves_the_source_alone() {
+        let source = "local a = 1\nlocal b = 2\n";
+        let patch = "@@ -1,2 +1,2 @@\n local a = 1\n-local b = 3\n+local b = 4\n";
+        let result = apply(source, patch);
+        assert_eq!(result.source, source);
+        assert_eq!(
+            result.failed_hunks,
+            ["@@ -1,2 +1,2 @@\n local a = 1\n-local b = 3\n+local b = 4"]
+        );
+    }
+
+    #[test]
+    fn applies_multiple_hunks() {
+        let old: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "line 18\nline 18.5\n");
+        let result = apply(&old, &unified_diff("a", &old, "b", &new));
+        assert!(result.failed_hunks.is_empty());
+        assert_eq!(result.source, new);
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let source = "local a = 1\r\nlocal b = 2\r\n";
+        let patch = "@@ -1,2 +1,3 @@\n local a = 1\n+local c = 3\n local b = 2\n";
+        assert_eq!(
+            apply(source, patch).source,
+            "local a = 1\r\nlocal c = 3\r\nlocal b = 2\r\n"
+        );
+    }
+
+    #[test]
+    fn keeps_a_missing_final_newline() {
+        let source = "local a = 1\nreturn a";
+        let patch = "@@ -1,2 +1,2 @@\n-local a = 1\n+local a = 2\n return a\n\\ No newline at end of file\n";
+        assert_eq!(apply(source, patch).source, "local a = 2\nreturn a");
+    }
+
+    #[test]
+    fn adds_and_removes_the_final_newline() {
+        for (old, new) in [("return 1", "return 1\n"), ("return 1\n", "return 1")] {
+            let patch = unified_diff("a", old, "b", new);
+            assert!(patch.contains("\\ No newline at end of file"), "{patch}");
+            assert_eq!(apply(old, &patch).source, new, "{patch}");
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A script collected from a subtree, as sent by the plugin
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ScriptEntry {
+    /// Instance names from the subtree root (exclusive) down to the script, empty for the root itself
+    pub path: Vec<String>,
+    pub class_name: String,
+    pub source: String,
+    /// Whether the script has scripts below it, in which case it is written as a folder `init` file
+    #[serde(default)]
+    pub has_children: bool,
+}
+
+/// A script that could not be written, with the reason
+#[derive(Debug, Serialize)]
+pub struct Skipped {
+    pub path: String,
+    pub reason: String,
+}
+
+fn extension(class_name: &str) -> Option<&'static str> {
+    match class_name {
+        "Script" => Some(".server.luau"),
+        "LocalScript" => Some(".client.luau"),
+        "ModuleScript" => Some(".luau"),
+        _ => None,
+    }
+}
+
+fn valid_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.ends_with('.')
+        && !name.chars().any(|c| {
+            c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+        })
+}
+
+/// Maps scripts to files relative to the export directory, Rojo style: `Name.server.luau` for Scripts,
+/// `Name.client.luau` for LocalScripts, `Name.luau` for ModuleScripts and `Name/init.*` for scripts with children.
+/// Scripts whose file would collide with an earlier one (case-insensitively) or whose names can't be used as file
+/// names are skipped.
+pub fn layout(scripts: &[ScriptEntry]) -> (Vec<(PathBuf, &ScriptEntry)>, Vec<Skipped>) {
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let mut seen = HashSet::new();
+    for script in scripts {
+        let display = script.path.join(".");
+        let Some(extension) = extension(&script.class_name) else {
+            skipped.push(Skipped {
+                path: display,
+                reason: format!("{} is not a script class", script.class_name),
+            });
+            continue;
+        };
+        if let Some(name) = script.path.iter().find(|name| !valid_file_name(name)) {
+            skipped.push(Skipped {
+                path: display,
+                reason: format!("{name:?} cannot be used as a file name"),
+            });
+            continue;
+        }
+
+        let mut file: PathBuf = script.path.iter().collect();
+        if script.has_children || script.path.is_empty() {
+            file.push(format!("init{extension}"));
+        } else {
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            file.set_file_name(format!("{name}{extension}"));
+        }
+
+        if !seen.insert(file.to_string_lossy().to_lowercase()) {
+            skipped.push(Skipped {
+                path: display,
+                reason: format!("{} is already used by another script", file.display()),
+            });
+            continue;
+        }
+        files.push((file, script));
+    }
+    (files, skipped)
+}
+
+/// Writes `files` below `dir`, returning how many were written. Fails before writing through a symlink that leads
+/// out of `dir`.
+pub fn write_all(dir: &Path, files: &[(PathBuf, &ScriptEntry)]) -> std::io::Result<usize> {
+    std::fs::create_dir_all(dir)?;
+    let base = dir.canonicalize()?;
+    for (file, script) in files {
+        let path = dir.join(file);
+        if !crate::config::resolves_inside(&base, &path) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{} resolves outside the files directory", file.display()),
+            ));
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, script.source.as_bytes())?;
+    }
+    Ok(files.len())
+}
+
+/// Maps a file name to the script's instance name and class, or `None` for files that aren't scripts
+fn parse_file_name(file_name: &str) -> Option<(&str, &'static str)> {
+    let stem = file_name
+        .strip_suffix(".luau")
+        .or_else(|| file_name.strip_suffix(".lua"))?;
+    if let Some(name) = stem.strip_suffix(".server") {
+        Some((name, "Script"))
+    } else if let Some(name) = stem.strip_suffix(".client") {
+        Some((name, "LocalScript"))
+    } else {
+        Some((stem, "ModuleScript"))
+    }
+}
+
+fn read_dir_into(
+    dir: &Path,
+    path: &mut Vec<String>,
+    scripts: &mut Vec<ScriptEntry>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        // Symlinks could point outside of the files directory
+        if file_type.is_symlink() || file_name.starts_with('.') {
+            continue;
+        }
+
+        if file_type.is_dir() {
+            path.push(file_name);
+            read_dir_into(&entry.path(), path, scripts)?;
+            path.pop();
+        } else if let Some((name, class_name)) = parse_file_name(&file_name) {
+            let mut script_path = path.clone();
+            if name != "init" {
+                script_path.push(name.to_owned());
+            }
+            scripts.push(ScriptEntry {
+                path: script_path,
+                class_name: class_name.to_owned(),
+                source: std::fs::read_to_string(entry.path())?,
+                has_children: false,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reads the `.lua` and `.luau` files below `dir` using the same layout as [`layout`]. Scripts are ordered
+/// parents first so `init` scripts are created before the scripts inside them.
+pub fn read_all(dir: &Path) -> std::io::Result<Vec<ScriptEntry>> {
+    let mut scripts = Vec::new();
+    read_dir_into(dir, &mut Vec::new(), &mut scripts)?;
+    scripts.sort_by(|a, b| {
+        a.path
+            .len()
+            .cmp(&b.path.len())
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(scripts)
+}
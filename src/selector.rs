@@ -0,0 +1,483 @@
+//! A small CSS-like selector syntax for finding instances, parsed here and matched by the plugin.
+//!
+//! ```text
+//! selectors  = selector ("," selector)*
+//! selector   = compound (combinator compound)*
+//! combinator = ">"                      child
+//!            | whitespace               descendant
+//! compound   = (ClassName | "*")? part* at least one of either
+//! part       = "#" word                 Name equals
+//!            | "." word                 has the CollectionService tag
+//!            | "[" "@"? key (op value)? "]"
+//! op         = "=" | "!=" | "*="        equals, differs, contains
+//! word       = identifier | quoted string
+//! value      = true | false | number | quoted string | bare word
+//! ```
+//!
+//! Class names match with IsA. Filters read a property, or an attribute with `@`, and without an operator match when
+//! it is set and not false. For example `Workspace > Model Part[Anchored=true]`, `#"Spawn Location"` or
+//! `Folder#Enemies > Model[@Boss]`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// How a compound relates to the one before it
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Combinator {
+    Child,
+    Descendant,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Present,
+    Eq,
+    Ne,
+    Contains,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Filter {
+    pub key: String,
+    /// Whether `key` names an attribute rather than a property
+    pub attribute: bool,
+    pub op: Op,
+    pub value: Option<Value>,
+}
+
+/// Conditions on a single instance, e.g. `Part#Base[Anchored]`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct Compound {
+    /// None for the first compound of a selector
+    pub combinator: Option<Combinator>,
+    pub class_name: Option<String>,
+    pub name: Option<String>,
+    pub tags: Vec<String>,
+    pub filters: Vec<Filter>,
+}
+
+/// Compounds from the outermost ancestor to the instance being matched
+pub type Selector = Vec<Compound>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Character offset into the query
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// The error followed by the query with a caret under the position
+    pub fn describe(&self, query: &str) -> String {
+        format!(
+            "Invalid selector: {self}\n{query}\n{}^",
+            " ".repeat(self.position)
+        )
+    }
+}
+
+/// Parses a comma separated list of selectors
+pub fn parse(query: &str) -> Result<Vec<Selector>, ParseError> {
+    let mut parser = Parser {
+        chars: query.chars().collect(),
+        pos: 0,
+    };
+    let mut selectors = vec![];
+    loop {
+        parser.skip_whitespace();
+        selectors.push(parser.selector()?);
+        match parser.peek() {
+            None => return Ok(selectors),
+            Some(',') => parser.pos += 1,
+            Some(c) => return Err(parser.error(format!("Unexpected {c}"))),
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            position: self.pos,
+            message,
+        }
+    }
+
+    /// An error naming what was expected and what was found instead
+    fn expected(&self, what: &str) -> ParseError {
+        match self.peek() {
+            Some(c) => self.error(format!("Expected {what}, found {c}")),
+            None => self.error(format!("Expected {what}, but the selector ended")),
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(&accept) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn selector(&mut self) -> Result<Selector, ParseError> {
+        let mut compounds = vec![self.compound(None)?];
+        loop {
+            let spaced = self.skip_whitespace();
+            let combinator = match self.peek() {
+                None | Some(',') => return Ok(compounds),
+                Some('>') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(_) if spaced => Combinator::Descendant,
+                Some(c) => return Err(self.error(format!("Unexpected {c}"))),
+            };
+            compounds.push(self.compound(Some(combinator))?);
+        }
+    }
+
+    fn compound(&mut self, combinator: Option<Combinator>) -> Result<Compound, ParseError> {
+        let start = self.pos;
+        let mut compound = Compound {
+            combinator,
+            ..Default::default()
+        };
+        if !self.eat('*') && self.peek().is_some_and(|c| c.is_alphabetic() || c == '_') {
+            compound.class_name = Some(self.take_while(is_word_char));
+        }
+        loop {
+            let part_start = self.pos;
+            if self.eat('#') {
+                let name = self.word("a name")?;
+                if compound.name.is_some() {
+                    return Err(ParseError {
+                        position: part_start,
+                        message: "An instance only has one name".to_string(),
+                    });
+                }
+                compound.name = Some(name);
+            } else if self.eat('.') {
+                compound.tags.push(self.word("a tag")?);
+            } else if self.eat('[') {
+                compound.filters.push(self.filter(part_start)?);
+            } else {
+                break;
+            }
+        }
+        if self.pos == start {
+            return Err(self.expected("a class name, *, #name, .tag or [filter]"));
+        }
+        Ok(compound)
+    }
+
+    /// An identifier or a quoted string
+    fn word(&mut self, what: &str) -> Result<String, ParseError> {
+        match self.peek() {
+            Some('"' | '\'') => self.quoted(),
+            Some(c) if is_word_char(c) => Ok(self.take_while(is_word_char)),
+            _ => Err(self.expected(what)),
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+        let quote = self.chars[start];
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None => {
+                    return Err(ParseError {
+                        position: start,
+                        message: "Unterminated string".to_string(),
+                    })
+                }
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(text);
+                }
+                Some('\\') if self.pos + 1 < self.chars.len() => {
+                    text.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// The inside of `[...]`, `open` being the position of the bracket
+    fn filter(&mut self, open: usize) -> Result<Filter, ParseError> {
+        self.skip_whitespace();
+        let attribute = self.eat('@');
+        let key = self.take_while(is_word_char);
+        if key.is_empty() {
+            return Err(self.expected(if attribute {
+                "an attribute name"
+            } else {
+                "a property name"
+            }));
+        }
+        self.skip_whitespace();
+        let op = match self.peek() {
+            Some('=') => Op::Eq,
+            Some('!') => Op::Ne,
+            Some('*') => Op::Contains,
+            _ => Op::Present,
+        };
+        if op != Op::Present {
+            self.pos += 1;
+            if op != Op::Eq && !self.eat('=') {
+                return Err(self.expected("="));
+            }
+        }
+
+        let value = if op == Op::Present {
+            None
+        } else {
+            self.skip_whitespace();
+            let value = self.value()?;
+            self.skip_whitespace();
+            Some(value)
+        };
+        if !self.eat(']') {
+            let expected = if op == Op::Present {
+                "=, !=, *= or ]".to_string()
+            } else {
+                format!("] to close the filter opened at column {}", open + 1)
+            };
+            return Err(self.expected(&expected));
+        }
+        Ok(Filter {
+            key,
+            attribute,
+            op,
+            value,
+        })
+    }
+
+    /// A quoted string, or a bare word read as a boolean, number or string
+    fn value(&mut self) -> Result<Value, ParseError> {
+        if matches!(self.peek(), Some('"' | '\'')) {
+            return self.quoted().map(Value::String);
+        }
+        let word = self.take_while(|c| is_word_char(c) || matches!(c, '.' | '-' | '+'));
+        if word.is_empty() {
+            return Err(self.expected("a value"));
+        }
+        Ok(match word.as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => word
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map_or(Value::String(word), Value::Number),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn single(query: &str) -> Selector {
+        let mut selectors = parse(query).unwrap();
+        assert_eq!(selectors.len(), 1, "{selectors:?}");
+        selectors.remove(0)
+    }
+
+    fn error_column(query: &str) -> (usize, String) {
+        let err = parse(query).unwrap_err();
+        (err.position + 1, err.message)
+    }
+
+    #[test]
+    fn combinators() {
+        let selector = single("Workspace > Model  Part");
+        let combinators: Vec<_> = selector.iter().map(|c| c.combinator).collect();
+        assert_eq!(
+            combinators,
+            [None, Some(Combinator::Child), Some(Combinator::Descendant)]
+        );
+        let classes: Vec<_> = selector.iter().map(|c| c.class_name.as_deref()).collect();
+        assert_eq!(classes, [Some("Workspace"), Some("Model"), Some("Part")]);
+        assert_eq!(single("Model>Part")[1].combinator, Some(Combinator::Child));
+    }
+
+    #[test]
+    fn selector_lists() {
+        let selectors = parse("Part, #Base ,Model").unwrap();
+        assert_eq!(selectors.len(), 3);
+        assert_eq!(selectors[1][0].name.as_deref(), Some("Base"));
+    }
+
+    #[test]
+    fn compound_parts() {
+        let compound = &single("*#Door.Locked.Red")[0];
+        assert_eq!(compound.class_name, None);
+        assert_eq!(compound.name.as_deref(), Some("Door"));
+        assert_eq!(compound.tags, ["Locked", "Red"]);
+    }
+
+    #[test]
+    fn property_and_attribute_filters() {
+        let filters = single("Part[Anchored][@Health=x]")[0].filters.clone();
+        assert_eq!(
+            filters,
+            [
+                Filter {
+                    key: "Anchored".to_string(),
+                    attribute: false,
+                    op: Op::Present,
+                    value: None,
+                },
+                Filter {
+                    key: "Health".to_string(),
+                    attribute: true,
+                    op: Op::Eq,
+                    value: Some(json!("x")),
+                },
+            ]
+        );
+        let ops: Vec<_> = single("[@Team != Red][Name*=Spawn][ Size = 4 ]")[0]
+            .filters
+            .iter()
+            .map(|f| (f.op, f.value.clone()))
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                (Op::Ne, Some(json!("Red"))),
+                (Op::Contains, Some(json!("Spawn"))),
+                (Op::Eq, Some(json!(4.0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn values() {
+        let value = |query: &str| single(query)[0].filters[0].value.clone().unwrap();
+        assert_eq!(value("[A=true]"), json!(true));
+        assert_eq!(value("[A=false]"), json!(false));
+        assert_eq!(value("[A=-1.5]"), json!(-1.5));
+        assert_eq!(value("[A=Enum.Material.Wood]"), json!("Enum.Material.Wood"));
+        assert_eq!(value("[A='true']"), json!("true"));
+    }
+
+    #[test]
+    fn quoted_strings_and_escapes() {
+        assert_eq!(
+            single(r#"#"Spawn Location""#)[0].name.as_deref(),
+            Some("Spawn Location")
+        );
+        assert_eq!(single(r#"#'it\'s'"#)[0].name.as_deref(), Some("it's"));
+        assert_eq!(
+            single(r#"[@Label="say \"hi\""]"#)[0].filters[0].value,
+            Some(json!(r#"say "hi""#))
+        );
+        assert_eq!(single(r#".'a\\b'"#)[0].tags, [r"a\b"]);
+    }
+
+    #[test]
+    fn error_columns() {
+        assert_eq!(
+            error_column("Model > [Anchored=true"),
+            (
+                23,
+                "Expected ] to close the filter opened at column 9, but the selector ended"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            error_column("Part#A#B"),
+            (7, "An instance only has one name".to_string())
+        );
+        assert_eq!(
+            error_column("Part #'open"),
+            (7, "Unterminated string".to_string())
+        );
+        assert_eq!(
+            error_column("Part[@]"),
+            (7, "Expected an attribute name, found ]".to_string())
+        );
+        assert_eq!(
+            error_column("Part[A!Red]"),
+            (8, "Expected =, found R".to_string())
+        );
+        assert_eq!(
+            error_column("Part[A B]"),
+            (8, "Expected =, !=, *= or ], found B".to_string())
+        );
+        assert_eq!(
+            error_column("Part > "),
+            (
+                8,
+                "Expected a class name, *, #name, .tag or [filter], but the selector ended"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            error_column("Part,"),
+            (
+                6,
+                "Expected a class name, *, #name, .tag or [filter], but the selector ended"
+                    .to_string()
+            )
+        );
+        assert_eq!(error_column("Part~Model"), (5, "Unexpected ~".to_string()));
+    }
+
+    #[test]
+    fn describe_points_at_the_column() {
+        let query = "Part[A";
+        let err = parse(query).unwrap_err();
+        assert_eq!(
+            err.describe(query),
+            "Invalid selector: Expected =, !=, *= or ], but the selector ended at column 7\nPart[A\n      ^"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Cancelled tool calls must not leave their command queued for the plugin
+
+mod common;
+
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn queued_commands(server: &mut common::Server, id: u64) -> Vec<Value> {
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": { "name": "get_queued_commands", "arguments": {} },
+    }));
+    let response = server.response(id);
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    let report: Value = serde_json::from_str(text).unwrap();
+    report["queued"].as_array().unwrap().clone()
+}
+
+#[test]
+fn cancelled_command_leaves_queue() {
+    let port = common::free_port().to_string();
+    let mut server = common::spawn_server(&["--port", &port]);
+    server.initialize();
+    server.response(1);
+
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "run_code", "arguments": { "command": "print('never run')" } },
+    }));
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(queued_commands(&mut server, 3).len(), 1);
+
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": { "requestId": 2, "reason": "test" },
+    }));
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(queued_commands(&mut server, 4), Vec::<Value>::new());
+}
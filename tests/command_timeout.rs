@@ -0,0 +1,136 @@
+//! Tool calls must fail instead of hanging when no plugin ever answers
+
+mod common;
+
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+#[test]
+fn unanswered_command_times_out() {
+    let port = common::free_port().to_string();
+    let mut server = common::spawn_server(&["--port", &port, "--command-timeout", "1"]);
+    server.initialize();
+
+    let started = Instant::now();
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "get_studio_mode", "arguments": {} },
+    }));
+    let response = server.response(2);
+
+    assert!(started.elapsed() < Duration::from_secs(20));
+    assert_eq!(response["result"]["isError"], true, "{response}");
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("did not complete within 1s"), "{text}");
+}
+
+#[test]
+fn command_fails_fast_without_plugin() {
+    let port = common::free_port().to_string();
+    let mut server = common::spawn_server(&["--port", &port, "--connection-timeout", "1"]);
+    server.initialize();
+    std::thread::sleep(Duration::from_secs(2));
+
+    let started = Instant::now();
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "get_studio_mode", "arguments": {} },
+    }));
+    let response = server.response(2);
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(response["result"]["isError"], true, "{response}");
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("Roblox Studio is not connected"), "{text}");
+}
+
+#[test]
+fn fails_fast_after_plugin_dies_mid_command() {
+    let port = common::free_port();
+    let mut server = common::spawn_server(&[
+        "--port",
+        &port.to_string(),
+        "--command-timeout",
+        "1",
+        "--connection-timeout",
+        "2",
+    ]);
+    server.initialize();
+
+    // The plugin picks up the command and is never heard from again
+    let poll = std::thread::spawn(move || common::plugin_http(port, "GET", "/request", None));
+    std::thread::sleep(Duration::from_millis(500));
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "get_studio_mode", "arguments": {} },
+    }));
+    let command: serde_json::Value = serde_json::from_str(&poll.join().unwrap()).unwrap();
+    assert!(command["args"]["GetStudioMode"].is_object(), "{command}");
+    let response = server.response(2);
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("did not complete within 1s"), "{text}");
+    let cancel_path = format!("/cancel/{}", command["id"].as_str().unwrap());
+    assert_eq!(common::plugin_http(port, "GET", &cancel_path, None), "true");
+
+    std::thread::sleep(Duration::from_secs(3));
+    let started = Instant::now();
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "tools/call",
+        "params": { "name": "get_studio_mode", "arguments": {} },
+    }));
+    let response = server.response(3);
+    assert!(started.elapsed() < Duration::from_millis(900));
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("Roblox Studio is not connected"), "{text}");
+    // The dead plugin will never answer, so its cancelled command isn't remembered anymore
+    assert_eq!(
+        common::plugin_http(port, "GET", &cancel_path, None),
+        "false"
+    );
+}
+
+#[test]
+fn unanswered_proxied_command_does_not_block_the_next() {
+    let port = common::free_port();
+    let args = ["--port", &port.to_string(), "--command-timeout", "2"];
+    let mut owner = common::spawn_server(&args);
+    owner.initialize();
+    owner.response(1);
+    // The port is taken, so this instance forwards its commands to the first one
+    let mut proxy = common::spawn_server(&args);
+    proxy.initialize();
+    proxy.response(1);
+
+    let call = |id: u64| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": "get_studio_mode", "arguments": {} },
+        })
+    };
+    proxy.send(call(2));
+    let response = proxy.response(2);
+    assert_eq!(response["result"]["isError"], true, "{response}");
+    std::thread::sleep(Duration::from_secs(1));
+
+    let poll = std::thread::spawn(move || common::plugin_http(port, "GET", "/request", None));
+    proxy.send(call(3));
+    let command: serde_json::Value = serde_json::from_str(&poll.join().unwrap()).unwrap();
+    let answer = json!({ "id": command["id"], "success": true, "response": "Edit" });
+    common::plugin_http(port, "POST", "/response", Some(&answer));
+
+    let response = proxy.response(3);
+    assert_eq!(
+        response["result"]["content"][0]["text"], "Edit",
+        "{response}"
+    );
+}
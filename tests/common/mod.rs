@@ -0,0 +1,145 @@
+//! Helpers for driving the server binary over stdio like an MCP client
+// Each test binary only uses some of the helpers
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a test waits for the server to answer before failing instead of hanging the suite
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct Server {
+    pub child: Child,
+    pub stdin: Option<ChildStdin>,
+    /// Lines the server writes to stdout, in order
+    pub lines: Receiver<String>,
+}
+
+/// A port nothing is listening on, so the server owns the plugin endpoint instead of proxying to another instance
+pub fn free_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+/// Makes an HTTP request to the plugin endpoint like the Studio plugin does and returns the response body
+pub fn plugin_http(port: u16, method: &str, path: &str, body: Option<&Value>) -> String {
+    // The server may still be binding its listener
+    let mut stream = (0..50)
+        .find_map(|_| {
+            TcpStream::connect(("127.0.0.1", port))
+                .inspect_err(|_| thread::sleep(Duration::from_millis(100)))
+                .ok()
+        })
+        .expect("plugin endpoint should accept connections");
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT)).unwrap();
+    let body = body.map(Value::to_string).unwrap_or_default();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default()
+}
+
+pub fn spawn_server(args: &[&str]) -> Server {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rbx-studio-mcp"))
+        .arg("--stdio")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("server should start");
+
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let (tx, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in stdout.lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    Server {
+        stdin: child.stdin.take(),
+        child,
+        lines,
+    }
+}
+
+impl Server {
+    pub fn send(&mut self, message: Value) {
+        let stdin = self.stdin.as_mut().expect("stdin is open");
+        writeln!(stdin, "{message}").unwrap();
+    }
+
+    /// Runs the MCP handshake
+    pub fn initialize(&mut self) {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": { "name": "integration-test", "version": "0" },
+            },
+        }));
+        self.send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+    }
+
+    /// Waits for the frame answering request `id`, panicking with everything the server wrote if it doesn't come
+    pub fn response(&self, id: u64) -> Value {
+        let deadline = Instant::now() + RESPONSE_TIMEOUT;
+        let mut seen = vec![];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = self.lines.recv_timeout(remaining).unwrap_or_else(|err| {
+                panic!("no response to request {id} ({err}), server wrote: {seen:#?}")
+            });
+            let frame: Value = serde_json::from_str(&line)
+                .unwrap_or_else(|e| panic!("stdout line is not JSON ({e}): {line}"));
+            if frame["id"] == id {
+                return frame;
+            }
+            seen.push(line);
+        }
+    }
+
+    /// Everything the server writes until it closes stdout, panicking if it is still writing after the deadline
+    pub fn remaining_lines(&self) -> Vec<String> {
+        let deadline = Instant::now() + RESPONSE_TIMEOUT;
+        let mut lines = vec![];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(remaining) {
+                Ok(line) => lines.push(line),
+                Err(mpsc::RecvTimeoutError::Disconnected) => return lines,
+                Err(err) => panic!("server didn't exit ({err}), it wrote: {lines:#?}"),
+            }
+        }
+    }
+
+    /// Closing stdin makes the server shut down
+    pub fn close(&mut self) {
+        self.stdin.take();
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
@@ -0,0 +1,38 @@
+//! File tools must not write outside the files directory through symlinks inside it
+#![cfg(unix)]
+
+mod common;
+
+use serde_json::json;
+use std::fs;
+
+#[test]
+fn new_files_below_a_symlink_out_of_the_files_dir_are_rejected() {
+    let root = std::env::temp_dir().join(format!("rbx-mcp-files-dir-{}", std::process::id()));
+    let (files_dir, outside) = (root.join("files"), root.join("outside"));
+    fs::create_dir_all(&files_dir).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    std::os::unix::fs::symlink(&outside, files_dir.join("logs")).unwrap();
+
+    let port = common::free_port().to_string();
+    let files_arg = files_dir.to_string_lossy().into_owned();
+    let mut server = common::spawn_server(&["--port", &port, "--files-dir", &files_arg]);
+    server.initialize();
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "export_output_log", "arguments": { "file": "logs/nested/output.log" } },
+    }));
+    let response = server.response(2);
+    let escaped = outside.join("nested").exists();
+    fs::remove_dir_all(&root).ok();
+
+    assert_eq!(response["result"]["isError"], true, "{response}");
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    assert!(
+        text.contains("Path logs/nested/output.log resolves outside the files directory"),
+        "{text}"
+    );
+    assert!(!escaped);
+}
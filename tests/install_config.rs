@@ -0,0 +1,182 @@
+//! The installer must merge its entry into existing client configs instead of replacing them
+// The installer opens a dialog on macOS and waits for a key press on Windows
+#![cfg(target_os = "linux")]
+
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// A temporary home directory, removed when dropped
+struct Home(PathBuf);
+
+impl std::ops::Deref for Home {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Home {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+/// A fresh home directory with a Cursor config holding `contents`
+fn home_with_cursor_config(test: &str, contents: &str) -> Home {
+    let home = std::env::temp_dir().join(format!("rbx-mcp-{test}-{}", std::process::id()));
+    fs::remove_dir_all(&home).ok();
+    fs::create_dir_all(home.join(".cursor")).unwrap();
+    fs::write(home.join(".cursor").join("mcp.json"), contents).unwrap();
+    Home(home)
+}
+
+/// Runs the installer against `home`, from `home` so the project `.cursor/mcp.json` it also sets up is the one in
+/// `home` rather than one in the checkout. Studio isn't installed here, so only the client configs are set up.
+fn install(home: &Path) -> Output {
+    install_with(home, &[])
+}
+
+fn install_with(home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rbx-studio-mcp"))
+        .args(args)
+        .current_dir(home)
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .env_remove("XDG_CONFIG_HOME")
+        .stdin(Stdio::null())
+        .output()
+        .expect("installer should run")
+}
+
+fn cursor_config(home: &Path) -> String {
+    fs::read_to_string(home.join(".cursor").join("mcp.json")).unwrap()
+}
+
+const THIRD_PARTY: &str = r#"{
+    "theme": "dark",
+    "mcpServers": {
+        "github": {
+            "command": "github-mcp",
+            "env": { "TOKEN": "secret" }
+        },
+        "filesystem": {
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-filesystem", "/tmp"]
+        }
+    }
+}
+"#;
+
+#[test]
+fn keeps_other_servers() {
+    let home = home_with_cursor_config("keeps-other-servers", THIRD_PARTY);
+    install(&home);
+
+    let contents = cursor_config(&home);
+    let config: Value = serde_json::from_str(&contents).unwrap();
+    let original: Value = serde_json::from_str(THIRD_PARTY).unwrap();
+    assert_eq!(config["theme"], "dark");
+    assert_eq!(
+        config["mcpServers"]["github"],
+        original["mcpServers"]["github"]
+    );
+    assert_eq!(
+        config["mcpServers"]["filesystem"],
+        original["mcpServers"]["filesystem"]
+    );
+    assert_eq!(
+        config["mcpServers"]["Roblox_Studio"],
+        json!({ "command": env!("CARGO_BIN_EXE_rbx-studio-mcp"), "args": ["--stdio"] })
+    );
+
+    // Entries keep their order and the file keeps its indentation
+    let servers: Vec<_> = config["mcpServers"].as_object().unwrap().keys().collect();
+    assert_eq!(servers, ["github", "filesystem", "Roblox_Studio"]);
+    assert!(contents.starts_with("{\n    \"theme\""), "{contents}");
+    assert!(contents.ends_with("}\n"), "{contents}");
+}
+
+#[test]
+fn second_install_changes_nothing() {
+    let home = home_with_cursor_config("second-install", THIRD_PARTY);
+    install(&home);
+    let first = cursor_config(&home);
+    install(&home);
+    assert_eq!(cursor_config(&home), first);
+}
+
+#[test]
+fn keeps_options_added_to_the_entry() {
+    let home = home_with_cursor_config(
+        "keeps-options",
+        r#"{ "mcpServers": { "Roblox_Studio": { "command": "/old/path", "args": ["--stdio", "--read-only"], "env": { "RBX_MCP_PORT": "45000" } } } }"#,
+    );
+    install(&home);
+
+    let config: Value = serde_json::from_str(&cursor_config(&home)).unwrap();
+    assert_eq!(
+        config["mcpServers"]["Roblox_Studio"],
+        json!({
+            "command": env!("CARGO_BIN_EXE_rbx-studio-mcp"),
+            "args": ["--stdio", "--read-only"],
+            "env": { "RBX_MCP_PORT": "45000" },
+        })
+    );
+}
+
+#[test]
+fn backs_up_malformed_config() {
+    let malformed = "{ \"mcpServers\": { \"github\": ";
+    let home = home_with_cursor_config("malformed", malformed);
+    let output = install(&home);
+
+    let backup = home.join(".cursor").join("mcp.json.bak");
+    assert_eq!(fs::read_to_string(&backup).unwrap(), malformed);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("{backup:?}")), "{stderr}");
+
+    let config: Value = serde_json::from_str(&cursor_config(&home)).unwrap();
+    assert!(
+        config["mcpServers"]["Roblox_Studio"].is_object(),
+        "{config}"
+    );
+}
+
+#[test]
+fn vscode_config_uses_its_own_format() {
+    let home = home_with_cursor_config("vscode", "{}");
+    let user_dir = home.join(".config").join("Code").join("User");
+    fs::create_dir_all(&user_dir).unwrap();
+    fs::write(
+        user_dir.join("mcp.json"),
+        r#"{ "servers": { "github": { "type": "http", "url": "https://example.com/mcp" } }, "inputs": [] }"#,
+    )
+    .unwrap();
+    install(&home);
+
+    let config: Value =
+        serde_json::from_str(&fs::read_to_string(user_dir.join("mcp.json")).unwrap()).unwrap();
+    assert_eq!(config["inputs"], json!([]));
+    assert_eq!(config["servers"]["github"]["type"], "http");
+    assert_eq!(
+        config["servers"]["Roblox_Studio"],
+        json!({ "type": "stdio", "command": env!("CARGO_BIN_EXE_rbx-studio-mcp"), "args": ["--stdio"] })
+    );
+    assert!(config.get("mcpServers").is_none(), "{config}");
+}
+
+#[test]
+fn dry_run_only_prints_the_diff() {
+    let home = home_with_cursor_config("dry-run", THIRD_PARTY);
+    let output = install_with(&home, &["--dry-run"]);
+
+    assert_eq!(cursor_config(&home), THIRD_PARTY);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let config_path = home.join(".cursor").join("mcp.json");
+    assert!(stderr.contains(&format!("{config_path:?}")), "{stderr}");
+    assert!(stderr.contains("+        \"Roblox_Studio\": {"), "{stderr}");
+    assert!(output.stdout.is_empty());
+}
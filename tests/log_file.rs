@@ -0,0 +1,29 @@
+//! A server started without --log-file still keeps its logs in a file
+
+mod common;
+
+use serde_json::{json, Value};
+
+#[test]
+fn logs_to_the_temp_directory_by_default() {
+    let port = common::free_port().to_string();
+    let mut server = common::spawn_server(&["--port", &port]);
+    server.initialize();
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "get_server_config", "arguments": {} },
+    }));
+
+    let response = server.response(2);
+    let report: Value =
+        serde_json::from_str(response["result"]["content"][0]["text"].as_str().unwrap()).unwrap();
+    let default = std::env::temp_dir().join("rbx-studio-mcp.log");
+    assert_eq!(
+        report["log_destination"],
+        format!("stderr and {}", default.display()),
+        "{report}"
+    );
+    assert!(default.exists());
+}
@@ -0,0 +1,61 @@
+//! Replaying a macro must not get around tools disabled on this server
+
+mod common;
+
+use serde_json::{json, Value};
+use std::fs;
+
+fn replay(test: &str, macro_steps: Value, extra_args: &[&str]) -> String {
+    let files_dir =
+        std::env::temp_dir().join(format!("rbx-mcp-macros-{test}-{}", std::process::id()));
+    fs::create_dir_all(files_dir.join("macros")).unwrap();
+    fs::write(
+        files_dir.join("macros").join("setup.json"),
+        macro_steps.to_string(),
+    )
+    .unwrap();
+
+    let port = common::free_port().to_string();
+    let files_arg = files_dir.to_string_lossy().into_owned();
+    let mut args = vec!["--port", &port, "--files-dir", &files_arg];
+    args.extend_from_slice(extra_args);
+    let mut server = common::spawn_server(&args);
+    server.initialize();
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "replay_macro", "arguments": { "name": "setup" } },
+    }));
+    let response = server.response(2);
+    fs::remove_dir_all(&files_dir).ok();
+
+    assert_eq!(response["result"]["isError"], true, "{response}");
+    response["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn steps_calling_disabled_tools_are_rejected() {
+    let steps = json!([
+        { "GetStudioMode": {} },
+        { "RunBatch": { "commands": [{ "command": "print('hi')" }] } },
+    ]);
+    let text = replay("disabled", steps, &["--disable-tool", "run_code"]);
+    assert!(
+        text.contains("Step 2 of macro setup calls run_batch, which is disabled"),
+        "{text}"
+    );
+}
+
+#[test]
+fn steps_that_are_not_tools_are_rejected() {
+    let steps = json!([{ "ApplyScripts": { "path": "game.ServerScriptService", "scripts": [] } }]);
+    let text = replay("not-tools", steps, &[]);
+    assert!(
+        text.contains("step 1 is ApplyScripts, which is not a tool"),
+        "{text}"
+    );
+}
@@ -0,0 +1,94 @@
+//! Selectors are parsed by the server, so syntax errors never reach Studio and the plugin gets a structured query
+
+mod common;
+
+use serde_json::{json, Value};
+
+fn call_query_selector(server: &mut common::Server, selector: &str) {
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "query_selector", "arguments": { "selector": selector } },
+    }));
+}
+
+fn tool_result(server: &common::Server) -> Value {
+    server.response(2)["result"].clone()
+}
+
+fn compound(
+    combinator: Option<&str>,
+    class_name: Option<&str>,
+    name: Option<&str>,
+    tags: &[&str],
+    filters: Value,
+) -> Value {
+    json!({ "combinator": combinator, "class_name": class_name, "name": name, "tags": tags, "filters": filters })
+}
+
+fn filter(key: &str, attribute: bool, op: &str, value: Value) -> Value {
+    json!({ "key": key, "attribute": attribute, "op": op, "value": value })
+}
+
+#[test]
+fn invalid_selector_reports_the_column() {
+    let port = common::free_port();
+    let mut server = common::spawn_server(&["--port", &port.to_string()]);
+    server.initialize();
+    call_query_selector(&mut server, "Model > [Anchored=true");
+
+    let result = tool_result(&server);
+    assert_eq!(result["isError"], true, "{result}");
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(
+        text.starts_with("Invalid selector: Expected ] to close the filter opened at column 9, but the selector ended at column 23"),
+        "{text}"
+    );
+    assert!(
+        text.ends_with("Model > [Anchored=true\n                      ^"),
+        "{text}"
+    );
+}
+
+#[test]
+fn plugin_receives_parsed_selector() {
+    let port = common::free_port();
+    let mut server = common::spawn_server(&["--port", &port.to_string()]);
+    server.initialize();
+    call_query_selector(
+        &mut server,
+        r#"Workspace > Model Part[Anchored=true], #"Spawn Location".Lobby[@Team!=Red]"#,
+    );
+
+    let command: Value =
+        serde_json::from_str(&common::plugin_http(port, "GET", "/request", None)).unwrap();
+    assert_eq!(
+        command["args"]["MatchSelector"]["selectors"],
+        json!([
+            [
+                compound(None, Some("Workspace"), None, &[], json!([])),
+                compound(Some("child"), Some("Model"), None, &[], json!([])),
+                compound(
+                    Some("descendant"),
+                    Some("Part"),
+                    None,
+                    &[],
+                    json!([filter("Anchored", false, "eq", json!(true))]),
+                ),
+            ],
+            [compound(
+                None,
+                None,
+                Some("Spawn Location"),
+                &["Lobby"],
+                json!([filter("Team", true, "ne", json!("Red"))]),
+            )],
+        ]),
+        "{command}"
+    );
+
+    let answer = json!({ "id": command["id"], "success": true, "response": r#"{"matches":[],"count":0,"truncated":false}"# });
+    common::plugin_http(port, "POST", "/response", Some(&answer));
+    assert_eq!(tool_result(&server)["structuredContent"]["count"], 0);
+}
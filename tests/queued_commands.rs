@@ -0,0 +1,44 @@
+//! get_queued_commands reports the calling session's commands, not those other server instances forward to this one
+
+mod common;
+
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn call(id: u64, name: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": { "name": name, "arguments": {} },
+    })
+}
+
+#[test]
+fn proxied_commands_are_not_reported_as_queued_by_the_owner() {
+    let port = common::free_port();
+    let args = ["--port", &port.to_string(), "--command-timeout", "10"];
+    let mut owner = common::spawn_server(&args);
+    owner.initialize();
+    owner.response(1);
+    // The port is taken, so this instance forwards its commands to the first one
+    let mut proxy = common::spawn_server(&args);
+    proxy.initialize();
+    proxy.response(1);
+
+    proxy.send(call(2, "get_studio_mode"));
+    owner.send(call(2, "get_selection"));
+    std::thread::sleep(Duration::from_secs(1));
+    owner.send(call(3, "get_queued_commands"));
+
+    let report: Value = serde_json::from_str(
+        owner.response(3)["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap(),
+    )
+    .unwrap();
+    let queued = report["queued"].as_array().unwrap();
+    assert_eq!(queued.len(), 1, "{report}");
+    assert_eq!(queued[0]["tool"], "GetSelection", "{report}");
+    assert_eq!(report["in_flight"], 0, "{report}");
+}
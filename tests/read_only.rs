@@ -0,0 +1,23 @@
+//! Read-only mode only offers tools that leave the place and Studio's state alone
+
+mod common;
+
+use serde_json::json;
+
+#[test]
+fn thumbnail_capture_is_not_offered() {
+    let port = common::free_port().to_string();
+    let mut server = common::spawn_server(&["--port", &port, "--read-only"]);
+    server.initialize();
+    server.send(json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }));
+
+    let response = server.response(2);
+    let names: Vec<&str> = response["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert!(names.contains(&"get_selection"), "{names:?}");
+    assert!(!names.contains(&"capture_selection_thumbnail"), "{names:?}");
+}
@@ -0,0 +1,24 @@
+//! get_server_resources counts the MCP sessions the server has open
+
+mod common;
+
+use serde_json::{json, Value};
+
+#[test]
+fn stdio_server_has_one_session() {
+    let port = common::free_port().to_string();
+    let mut server = common::spawn_server(&["--port", &port]);
+    server.initialize();
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "get_server_resources", "arguments": {} },
+    }));
+
+    let response = server.response(2);
+    let report: Value =
+        serde_json::from_str(response["result"]["content"][0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(report["open_sessions"], 1, "{report}");
+    assert_eq!(report["queued_commands"], 0, "{report}");
+}
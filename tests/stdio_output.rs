@@ -0,0 +1,24 @@
+//! Everything the server writes to stdout in stdio mode must be a JSON-RPC frame, clients fail to parse anything else
+
+mod common;
+
+use serde_json::json;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn stdout_only_contains_json_rpc_frames() {
+    let mut server = common::spawn_server(&["--port", &common::free_port().to_string()]);
+    server.initialize();
+    server.send(json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }));
+    thread::sleep(Duration::from_secs(2));
+    server.close();
+
+    let lines = server.remaining_lines();
+    assert!(!lines.is_empty(), "server wrote nothing to stdout");
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        let frame: serde_json::Value = serde_json::from_str(line)
+            .unwrap_or_else(|e| panic!("stdout line is not JSON ({e}): {line}"));
+        assert_eq!(frame["jsonrpc"], "2.0", "not a JSON-RPC frame: {line}");
+    }
+}
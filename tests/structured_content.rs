@@ -0,0 +1,45 @@
+//! Plugin responses that are JSON objects must reach clients as structured content, with the text kept as a fallback
+
+mod common;
+
+use serde_json::{json, Value};
+
+/// Calls run_code and answers it as the plugin with `output`, returning the tool result
+fn run_code_answered_with(output: &str) -> Value {
+    let port = common::free_port();
+    let mut server = common::spawn_server(&["--port", &port.to_string()]);
+    server.initialize();
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "run_code", "arguments": { "command": "print('hi')" } },
+    }));
+
+    let command: Value =
+        serde_json::from_str(&common::plugin_http(port, "GET", "/request", None)).unwrap();
+    let answer = json!({ "id": command["id"], "success": true, "response": output });
+    common::plugin_http(port, "POST", "/response", Some(&answer));
+
+    server.response(2)["result"].clone()
+}
+
+#[test]
+fn json_object_response_is_structured() {
+    let result = run_code_answered_with(r#"{"parts": 3}"#);
+    assert_eq!(
+        result["structuredContent"],
+        json!({ "parts": 3 }),
+        "{result}"
+    );
+    assert_eq!(result["content"][0]["text"], r#"{"parts": 3}"#);
+    assert_eq!(result["_meta"]["json"], true);
+}
+
+#[test]
+fn text_response_stays_text() {
+    let result = run_code_answered_with("hi");
+    assert!(result.get("structuredContent").is_none(), "{result}");
+    assert_eq!(result["content"][0]["text"], "hi");
+    assert_eq!(result["_meta"]["json"], false);
+}